@@ -5,7 +5,7 @@ export function view_mat(arg0: Float32Array): Float32Array;
 
 export function model_mat(arg0: Float32Array, arg1: number): Float32Array;
 
-export function proj_mat(arg0: Float32Array, arg1: Float32Array, arg2: number, arg3: number, arg4: number, arg5: number, arg6: number, arg7: number): Float32Array;
+export function proj_mat(arg0: Float32Array, arg1: Float32Array, arg2: number, arg3: number, arg4: number, arg5: number, arg6: number): Float32Array;
 
 export function rotator(arg0: Float32Array): Float32Array;
 
@@ -106,11 +106,11 @@ export function model_mat(arg0, arg1) {
     return realRet;
 }
 
-export function proj_mat(arg0, arg1, arg2, arg3, arg4, arg5, arg6, arg7) {
+export function proj_mat(arg0, arg1, arg2, arg3, arg4, arg5, arg6) {
     const [ptr0, len0] = passArrayF32ToWasm(arg0);
     const [ptr1, len1] = passArrayF32ToWasm(arg1);
     const retptr = globalArgumentPtr();
-    wasm.proj_mat(retptr, ptr0, len0, ptr1, len1, arg2, arg3, arg4, arg5, arg6, arg7);
+    wasm.proj_mat(retptr, ptr0, len0, ptr1, len1, arg2, arg3, arg4, arg5, arg6);
     const mem = getUint32Memory();
     const ptr = mem[retptr / 4];
     const len = mem[retptr / 4 + 1];
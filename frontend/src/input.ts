@@ -1,6 +1,7 @@
 // Handles keyboard and mouse input; mirrors input.rs.
 
 import * as state from "./state"
+import {Camera} from "./types"
 import {addVecs4, dotMV4, mulVConst4} from "./util"
 
 export function handlePressed(makeRotator: Function, pressed: number[], deltaT: number,
@@ -177,12 +178,45 @@ export function handlePressed(makeRotator: Function, pressed: number[], deltaT:
     }
 }
 
+function nextProjection(cam: Camera) {
+    // Cycle the 4d projection mode, with default parameters; mirrors Projection4::next.
+    switch(cam.projection_mode) {
+        case 0:
+            cam.projection_mode = 1
+            cam.projection_param = 2.
+            break
+        case 1:
+            cam.projection_mode = 2
+            cam.projection_param = 0.
+            break
+        default:
+            cam.projection_mode = 0
+            cam.projection_param = 0.
+            break
+    }
+}
+
+function handleNewlyPressed(code: number) {
+    // Toggles, which act once per key press rather than each frame it's held.
+    switch(code) {
+        case 9:  // Tab
+            state.scene.wireframe = !state.scene.wireframe
+            break
+        case 80:  // P
+            nextProjection(state.scene.cam)
+            break
+        default:
+            break
+    }
+}
+
 export function handleKeyDown(event: any) {
     // Prevent scrolling etc behavior from keys we use.
-    if ([87, 83, 68, 65, 32, 67, 17, 82, 70, 38, 40, 39, 37, 59, 81, 45, 46, 36,35, 33, 34 ].
+    if ([87, 83, 68, 65, 32, 67, 17, 82, 70, 38, 40, 39, 37, 59, 81, 45, 46, 36,35, 33, 34, 9, 80].
         indexOf(event.keyCode) > -1) { event.preventDefault() }
     if (state.currentlyPressedKeys.indexOf(event.keyCode) === -1) {
         state.currentlyPressedKeys.push(event.keyCode)
+        handleNewlyPressed(event.keyCode)
     }
 }
 
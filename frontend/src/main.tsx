@@ -210,7 +210,7 @@ rust.then(
 
             (cam: Camera) => r.proj_mat(
                 cam.position, new Float32Array(cam.θ), cam.fov, cam.aspect, cam.aspect_4, cam.near,
-                cam.far, cam.fourd_proj_dist
+                cam.far
         ).slice()
         )
         ReactDOM.render(<Main />, document.getElementById('root') as HTMLElement)
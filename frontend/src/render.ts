@@ -129,8 +129,10 @@ function drawScene(
             // Tell WebGL how to pull out the colors from the color buffer
             // into the vertexColor attribute.
 
-            // Tell WebGL which indices to use to index the vertices
-            gl.bindBuffer(gl.ELEMENT_ARRAY_BUFFER, staticBuffers.indexBuffers.get(s_id))
+            // Tell WebGL which indices to use to index the vertices; line pairs
+            // for wireframes, or triangles.
+            const indexBuffers = state.scene.wireframe ? staticBuffers.edgeBuffers : staticBuffers.indexBuffers
+            gl.bindBuffer(gl.ELEMENT_ARRAY_BUFFER, indexBuffers.get(s_id))
 
             // Tell WebGL to use our program when drawing
             gl.useProgram(programInfo.program)
@@ -175,13 +177,20 @@ function drawScene(
                 state.scene.lighting.diffuse_intensity)
             gl.uniform1f(programInfo.uniformLocations.colorMax, state.scene.color_max)
             gl.uniform1f(programInfo.uniformLocations.shapeOpacity, shape.opacity)
+            gl.uniform1i(programInfo.uniformLocations.projectionMode,
+                state.scene.cam.projection_mode)
+            gl.uniform1f(programInfo.uniformLocations.projectionParam,
+                state.scene.cam.projection_param)
 
             {
                 const type = gl.UNSIGNED_SHORT
                 const offset = 0
-                const vertexCount = shape.mesh.tris.length
 
-                gl.drawElements(gl.TRIANGLES, vertexCount, type, offset)
+                if (state.scene.wireframe) {
+                    gl.drawElements(gl.LINES, shape.mesh.edges.length * 2, type, offset)
+                } else {
+                    gl.drawElements(gl.TRIANGLES, shape.mesh.tris.length, type, offset)
+                }
             }
         }
     )
@@ -232,6 +241,7 @@ export function makeStaticBuffers(gl: WebGLRenderingContext, shapes_: Map<number
     let indexModifier = 0
     let tri_indices, indexBuffer, vertexBuffer, normalBuffer
     let indexBuffers = new Map()
+    let edgeBuffers = new Map()
     let normalBuffers = new Map()
     let vertexBuffers = new Map()
     // todo do we need to update normals each frame, or is once per shape suffient?
@@ -255,9 +265,14 @@ export function makeStaticBuffers(gl: WebGLRenderingContext, shapes_: Map<number
 
             vertices = []
             normals = []
+            // Vertices are repeated for each face they're in; edges use the first copy.
+            let firstCorner = new Map()
+            let corner = 0
             for (let i=0; i < shape.mesh.faces_vert.length; i++) {
                 face = shape.mesh.faces_vert[i]
                 for (let vertId of face) {
+                    if (!firstCorner.has(vertId)) { firstCorner.set(vertId, corner) }
+                    corner += 1
                     vertex = (shape.mesh.vertices.get(vertId) as any).position
                     for (let coord = 0; coord < 4; coord++) {  // Iterate through each coord.
                         vertices.push(vertex[coord])
@@ -266,6 +281,15 @@ export function makeStaticBuffers(gl: WebGLRenderingContext, shapes_: Map<number
                 }
             }
 
+            let edgeIndices: number[] = []
+            for (let [a, b] of shape.mesh.edges) {
+                edgeIndices.push(firstCorner.get(a), firstCorner.get(b))
+            }
+            const edgeBuffer = gl.createBuffer()
+            gl.bindBuffer(gl.ELEMENT_ARRAY_BUFFER, edgeBuffer)
+            gl.bufferData(gl.ELEMENT_ARRAY_BUFFER, new Uint16Array(edgeIndices), gl.STATIC_DRAW)
+            edgeBuffers.set(s_id, edgeBuffer)
+
             vertexBuffer = gl.createBuffer()
             // ARRAY_BUFFER is used for indexed content; we don't need to take
             // triangles into account.
@@ -286,6 +310,7 @@ export function makeStaticBuffers(gl: WebGLRenderingContext, shapes_: Map<number
 
     return {
         indexBuffers: indexBuffers,
+        edgeBuffers: edgeBuffers,
         vertexBuffers: vertexBuffers,
         normalBuffers: normalBuffers,
         skybox: skyboxTexBuffer,
@@ -422,6 +447,8 @@ export function main(viewMatMaker: Function, modelMatMaker: Function,
             diffuseIntensity: gl.getUniformLocation(shaderProgram, 'u_diffuse_intensity'),
             colorMax: gl.getUniformLocation(shaderProgram, 'u_color_max'),
             shapeOpacity: gl.getUniformLocation(shaderProgram, 'u_shape_opacity'),
+            projectionMode: gl.getUniformLocation(shaderProgram, 'u_projection_mode'),
+            projectionParam: gl.getUniformLocation(shaderProgram, 'u_projection_param'),
         },
     }
 
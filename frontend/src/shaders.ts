@@ -22,6 +22,9 @@ export const vsSource = `
     
     uniform float u_color_max;
     uniform float u_shape_opacity;
+
+    uniform int u_projection_mode;
+    uniform float u_projection_param;
            
     varying vec4 v_color;
     
@@ -53,11 +56,32 @@ export const vsSource = `
         return u_diffuse_color * diffuse_weight * u_diffuse_intensity;
     }
 
+    // See PROJECT_4D_GLSL in transforms.rs, which this must match.
+    bool project_4d(vec4 pt, int mode, float param, out vec3 projected) {
+        float scale = 1.;
+        if (mode == 1) {
+            float denom = param + pt[3];
+            if (denom <= 0.) { return false; }
+            scale = param / denom;
+        } else if (mode == 2) {
+            float denom = length(pt) - pt[3];
+            if (denom <= 0.) { return false; }
+            scale = length(pt) / denom;
+        }
+        projected = pt.xyz * scale;
+        return true;
+    }
+
     void main() {
         vec4 positioned_pt = (u_model * position) + u_shape_position;
         positioned_pt = u_view * (positioned_pt - u_cam_position);
         
-        gl_Position = u_proj * positioned_pt;
+        vec3 projected;
+        if (project_4d(positioned_pt, u_projection_mode, u_projection_param, projected)) {
+            gl_Position = u_proj * vec4(projected, 0.);
+        } else {
+            gl_Position = vec4(0., 0., -2., 1.);
+        }
 
         vec4 fourd_color = find_fourd_color(positioned_pt);
         vec4 diffuse_color = find_diffuse_color();
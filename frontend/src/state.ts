@@ -46,7 +46,8 @@ const cam: Camera = {
     aspect_4: 1.,
     near: 1.,
     far: .1,
-    fourd_proj_dist: 1.,
+    projection_mode: 0,
+    projection_param: 0.,
 }
 
 const lighting: Lighting = {
@@ -64,7 +65,8 @@ const defaultScene =  {
         cam_type: "free",
         lighting: lighting,
         color_max: 1.,
-        sensitivities: [0.1, 0.1, 0.1]
+        sensitivities: [0.1, 0.1, 0.1],
+        wireframe: false,
     }
 // todo temp to avoid an uninitialized scene var.
 
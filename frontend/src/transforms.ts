@@ -159,6 +159,6 @@ export function makeProjMat(cam: Camera): Float32Array {
         0., 2.*n / (t-b), (t+b) / (t-b), 0.,
         0., 0., -(f+n) / (f-n), -(2.*f*n) / (f-n) + (-f-n) / (f-n),
         // 0., 0., -(f+n) / (f-n), -(2.*f*n) / (f-n),
-        0., 0., 1., cam.fourd_proj_dist * 2.,
+        0., 0., 1., 0.,
     ])
 }
\ No newline at end of file
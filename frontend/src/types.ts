@@ -18,13 +18,45 @@ export class Mesh {
     faces_vert: Uint16Array[]  // Indicies of vertexes.
     normals: Normal[]
     tris: number[]
+    edges: [number, number][]  // Pairs of vertex ids, for drawing wireframes.
 
-    constructor(vertices: Map<number, Vertex>, faces_vert: Uint16Array[], normals: Normal[]) {
+    constructor(vertices: Map<number, Vertex>, faces_vert: Uint16Array[], normals: Normal[],
+                tris?: number[], edges?: [number, number][]) {
         this.vertices = vertices
         this.faces_vert = faces_vert
         this.normals = normals
 
-        this.makeTris()
+        // Meshes from Rust come with their triangles, which handle concave faces.
+        if (tris) {
+            this.tris = tris
+        } else {
+            this.makeTris()
+        }
+
+        if (edges) {
+            this.edges = edges
+        } else {
+            this.makeEdges()
+        }
+    }
+
+    makeEdges() {
+        // Find the unique edges of the faces, with the lower id first. Mirrors
+        // Mesh::edges in Rust.
+        let seen = new Set()
+        let result: [number, number][] = []
+        for (let face of this.faces_vert) {
+            for (let i=0; i < face.length; i++) {
+                const a = face[i], b = face[(i + 1) % face.length]
+                const edge: [number, number] = a < b ? [a, b] : [b, a]
+                const key = edge[0] + ',' + edge[1]
+                if (!seen.has(key)) {
+                    seen.add(key)
+                    result.push(edge)
+                }
+            }
+        }
+        this.edges = result
     }
 
      makeTris() {
@@ -51,7 +83,12 @@ export class Mesh {
             } else if (face.length === 2) {
                 throw "Faces must have len 3 or more."
             } else {
-                throw "Error: Haven't implemented faces with vertex counds higher than four."
+                // Fan out from the first vertex. Only valid for convex faces.
+                for (let j=1; j < face.length - 1; j++) {
+                    result.push(current_i)
+                    result.push(current_i + j)
+                    result.push(current_i + j + 1)
+                }
             }
             current_i += face.length
         }
@@ -103,7 +140,9 @@ export interface Camera {
     aspect_4: number
     near: number
     far: number
-    fourd_proj_dist: number
+    // 0 for orthographic, 1 for perspective, 2 for stereographic. See Projection4.
+    projection_mode: number
+    projection_param: number
 }
 
 export interface Source {
@@ -128,6 +167,7 @@ export interface Scene {
     color_max: number, // distance thresh for max 4d-color indicator.
     lighting: Lighting,
     sensitivities: [number, number, number]
+    wireframe: boolean  // Draw edges instead of faces.
 }
 
 export interface MainState {
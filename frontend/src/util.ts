@@ -143,7 +143,9 @@ export function deserSceneLib(rawLib: any) : Map<number, Scene> {
             mesh = new Mesh(
                 vertices,
                 shape.mesh.faces_vert.map((fv: any) => new Uint16Array(fv)),
-                shape.mesh.normals.map((n: any) => { return { normal: n }})
+                shape.mesh.normals.map((n: any) => { return { normal: n }}),
+                shape.mesh.tris,
+                shape.mesh.edges,
             )
 
             shapes.set(parseInt(s_id), new Shape(
@@ -164,7 +166,8 @@ export function deserSceneLib(rawLib: any) : Map<number, Scene> {
             aspect_4: scene.cam.aspect_4,
             near: scene.cam.near,
             far: scene.cam.far,
-            fourd_proj_dist: scene.cam.fourd_proj_dist,
+            projection_mode: scene.cam.projection_mode,
+            projection_param: scene.cam.projection_param,
         }
 
         lighting = {
@@ -183,7 +186,8 @@ export function deserSceneLib(rawLib: any) : Map<number, Scene> {
                 cam_type: scene.cam_type.toLowerCase(),
                 color_max: scene.color_max,
                 lighting,
-                sensitivities: scene.sensitivities
+                sensitivities: scene.sensitivities,
+                wireframe: scene.wireframe,
             }
         )
     })
@@ -0,0 +1,367 @@
+// Convex hulls of 4d point sets. We build a triangulated hull incrementally
+// (beneath-beyond), merge coplanar tetrahedral facets into the hull's 3d cells,
+// then read off the polygonal 2d faces where neighboring cells meet.
+// Geometry is done in f64 internally, since symmetric polytopes like the 120-cell
+// have many points that are exactly coplanar.
+
+use std::collections::HashMap;
+use std::{error, fmt};
+
+type Vec4 = [f64; 4];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum HullError {
+    // Point sets without a 4d hull.
+    TooFewPoints,  // Fewer than the five a 4-simplex needs.
+    NonFinitePoint(usize),
+    // The points are all at the same place, or lie on a line, plane or 3d hyperplane.
+    Flat,
+}
+
+impl fmt::Display for HullError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HullError::TooFewPoints => write!(f, "The convex hull needs at least five points."),
+            HullError::NonFinitePoint(i) => write!(f, "Point {} has a non-finite coordinate.", i),
+            HullError::Flat => write!(f, "The convex hull needs points that span all four dimensions."),
+        }
+    }
+}
+
+impl error::Error for HullError {}
+
+pub struct HullCell {
+    // A 3d cell of the hull; the boundary region lying on one hyperplane.
+    pub normal: Vec4,  // Outward unit normal of the cell's hyperplane.
+    pub offset: f64,  // normal · p for any point p in the cell.
+    pub verts: Vec<usize>,  // Indices into the input points.
+}
+
+pub struct HullFace {
+    // A 2d face of the hull. Vertices are in order around the polygon.
+    pub verts: Vec<usize>,
+    pub cells: (usize, usize),  // The two cells that meet at this face.
+}
+
+pub struct Hull {
+    pub verts: Vec<usize>,  // Input indices of points that are vertices of the hull.
+    pub cells: Vec<HullCell>,
+    pub faces: Vec<HullFace>,
+}
+
+struct Facet {
+    // A tetrahedron of the triangulated hull.
+    verts: [usize; 4],
+    normal: Vec4,
+    offset: f64,
+    size: f64,  // Proportional to volume; thin facets have less reliable normals.
+}
+
+fn sub(a: &Vec4, b: &Vec4) -> Vec4 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]]
+}
+
+fn dot(a: &Vec4, b: &Vec4) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3]
+}
+
+fn scale(a: &Vec4, s: f64) -> Vec4 {
+    [a[0] * s, a[1] * s, a[2] * s, a[3] * s]
+}
+
+fn norm(a: &Vec4) -> f64 {
+    dot(a, a).sqrt()
+}
+
+fn normal_3(a: &Vec4, b: &Vec4, c: &Vec4) -> Vec4 {
+    // Generalized cross product: A vector perpendicular to a, b and c. Each
+    // component is a signed 3x3 minor of the matrix with rows a, b, c.
+    let minor = |i: usize, j: usize, k: usize| {
+        a[i] * (b[j] * c[k] - b[k] * c[j]) -
+            a[j] * (b[i] * c[k] - b[k] * c[i]) +
+            a[k] * (b[i] * c[j] - b[j] * c[i])
+    };
+    [minor(1, 2, 3), -minor(0, 2, 3), minor(0, 1, 3), -minor(0, 1, 2)]
+}
+
+fn residual(v: &Vec4, basis: &[Vec4]) -> Vec4 {
+    // Remove the components of v along an orthonormal basis.
+    let mut result = *v;
+    for b in basis {
+        result = sub(&result, &scale(b, dot(&result, b)));
+    }
+    result
+}
+
+fn make_facet(points: &[Vec4], verts: [usize; 4], interior: &Vec4) -> Facet {
+    let p0 = &points[verts[0]];
+    let mut normal = normal_3(&sub(&points[verts[1]], p0), &sub(&points[verts[2]], p0),
+                              &sub(&points[verts[3]], p0));
+    let len = norm(&normal);
+    normal = scale(&normal, 1. / len);
+
+    // Point the normal away from the inside of the hull.
+    if dot(&normal, interior) > dot(&normal, p0) {
+        normal = scale(&normal, -1.);
+    }
+
+    Facet { verts, normal, offset: dot(&normal, p0), size: len }
+}
+
+fn rank(vectors: &[Vec4], tol: f64) -> usize {
+    // The number of linearly independent vectors among unit vectors.
+    let mut basis: Vec<Vec4> = Vec::new();
+    for v in vectors {
+        let r = residual(v, &basis);
+        let len = norm(&r);
+        if len > tol {
+            basis.push(scale(&r, 1. / len));
+        }
+    }
+    basis.len()
+}
+
+fn initial_simplex(points: &[Vec4], eps: f64) -> Result<[usize; 5], HullError> {
+    // Find five points that span all four dimensions, greedily picking whichever
+    // point is furthest from the span of the ones picked so far.
+    let mut result = [0; 5];
+    let mut basis: Vec<Vec4> = Vec::new();
+
+    for dim in 1..5 {
+        let mut best = (0, 0.);
+        for (i, point) in points.iter().enumerate() {
+            let dist = norm(&residual(&sub(point, &points[result[0]]), &basis));
+            if dist > best.1 {
+                best = (i, dist);
+            }
+        }
+        // Coincident points give eps = 0, so this has to include equality.
+        if best.1 <= eps {
+            return Err(HullError::Flat)
+        }
+        result[dim] = best.0;
+        let r = residual(&sub(&points[best.0], &points[result[0]]), &basis);
+        basis.push(scale(&r, 1. / best.1));
+    }
+    Ok(result)
+}
+
+fn sorted_ridge(a: usize, b: usize, c: usize) -> [usize; 3] {
+    let mut ridge = [a, b, c];
+    ridge.sort();
+    ridge
+}
+
+fn facet_ridges(facet: &Facet) -> [[usize; 3]; 4] {
+    let v = facet.verts;
+    [
+        sorted_ridge(v[0], v[1], v[2]),
+        sorted_ridge(v[0], v[1], v[3]),
+        sorted_ridge(v[0], v[2], v[3]),
+        sorted_ridge(v[1], v[2], v[3]),
+    ]
+}
+
+fn triangulate(points: &[Vec4], eps: f64) -> Result<Vec<Facet>, HullError> {
+    // Beneath-beyond: Start with a simplex, and add points one at a time. Facets
+    // the new point can see are removed, and the point is joined to the horizon
+    // of ridges between the visible and hidden facets.
+    let simplex = initial_simplex(points, eps)?;
+
+    let mut interior = [0.; 4];
+    for i in &simplex {
+        interior = [interior[0] + points[*i][0] / 5., interior[1] + points[*i][1] / 5.,
+                    interior[2] + points[*i][2] / 5., interior[3] + points[*i][3] / 5.];
+    }
+
+    let mut facets = Vec::new();
+    for skip in 0..5 {
+        let mut verts = [0; 4];
+        let mut j = 0;
+        for (i, vert) in simplex.iter().enumerate() {
+            if i != skip {
+                verts[j] = *vert;
+                j += 1;
+            }
+        }
+        facets.push(make_facet(points, verts, &interior));
+    }
+
+    for (i, point) in points.iter().enumerate() {
+        if simplex.contains(&i) { continue }
+
+        let (visible, hidden): (Vec<Facet>, Vec<Facet>) = facets.into_iter()
+            .partition(|facet| dot(&facet.normal, point) - facet.offset > eps);
+        facets = hidden;
+
+        if visible.is_empty() { continue }  // The point's inside the hull.
+
+        // Ridges shared by two visible facets are interior to the visible region;
+        // the ones that only appear once form the horizon.
+        let mut ridge_counts = HashMap::new();
+        for facet in &visible {
+            for ridge in facet_ridges(facet).iter() {
+                *ridge_counts.entry(*ridge).or_insert(0) += 1;
+            }
+        }
+
+        let mut horizon: Vec<[usize; 3]> = ridge_counts.into_iter()
+            .filter(|(_, count)| *count == 1)
+            .map(|(ridge, _)| ridge)
+            .collect();
+        horizon.sort();
+
+        for ridge in &horizon {
+            facets.push(make_facet(points, [ridge[0], ridge[1], ridge[2], i], &interior));
+        }
+    }
+
+    Ok(facets)
+}
+
+fn order_polygon(points: &[Vec4], verts: &mut Vec<usize>) {
+    // Sort a planar polygon's vertices by angle around its centroid.
+    let n = verts.len() as f64;
+    let mut centroid = [0.; 4];
+    for v in verts.iter() {
+        centroid = [centroid[0] + points[*v][0] / n, centroid[1] + points[*v][1] / n,
+                    centroid[2] + points[*v][2] / n, centroid[3] + points[*v][3] / n];
+    }
+
+    let u = sub(&points[verts[0]], &centroid);
+    let u = scale(&u, 1. / norm(&u));
+
+    // Use whichever vertex is furthest from the u axis for the second axis.
+    let mut w = [0.; 4];
+    for v in verts.iter() {
+        let r = residual(&sub(&points[*v], &centroid), &[u]);
+        if norm(&r) > norm(&w) {
+            w = r;
+        }
+    }
+    let w = scale(&w, 1. / norm(&w));
+
+    let angle = |v: &usize| {
+        let d = sub(&points[*v], &centroid);
+        dot(&d, &w).atan2(dot(&d, &u))
+    };
+    verts.sort_by(|a, b| angle(a).partial_cmp(&angle(b)).unwrap());
+}
+
+pub fn convex_hull(points: &[Vec4]) -> Result<Hull, HullError> {
+    // Find the cells and polygonal faces of the convex hull of a 4d point set.
+    if points.len() < 5 {
+        return Err(HullError::TooFewPoints)
+    }
+    if let Some(i) = points.iter().position(|p| p.iter().any(|c| !c.is_finite())) {
+        return Err(HullError::NonFinitePoint(i))
+    }
+
+    let extent = points.iter()
+        .fold(0., |acc: f64, p| acc.max(p[0].abs()).max(p[1].abs()).max(p[2].abs()).max(p[3].abs()));
+    let eps = extent * 1e-5;
+
+    let facets = triangulate(points, eps)?;
+
+    // Cells are made of coplanar facets. Join neighboring facets when either one's
+    // hyperplane passes through the other's vertices; checking both ways copes
+    // with thin facets, whose normals are less accurate.
+    let mut ridge_facets = HashMap::new();
+    for (facet_id, facet) in facets.iter().enumerate() {
+        for ridge in facet_ridges(facet).iter() {
+            ridge_facets.entry(*ridge).or_insert_with(Vec::new).push(facet_id);
+        }
+    }
+
+    let on_plane = |facet: &Facet, other: &Facet| other.verts.iter()
+        .all(|v| (dot(&facet.normal, &points[*v]) - facet.offset).abs() < eps);
+
+    let mut parents: Vec<usize> = (0..facets.len()).collect();
+    fn root(parents: &mut Vec<usize>, mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+
+    let mut ridges: Vec<&Vec<usize>> = ridge_facets.values().filter(|f| f.len() == 2).collect();
+    ridges.sort();
+    for facet_ids in &ridges {
+        let (a, b) = (facet_ids[0], facet_ids[1]);
+        if on_plane(&facets[a], &facets[b]) || on_plane(&facets[b], &facets[a]) {
+            let (root_a, root_b) = (root(&mut parents, a), root(&mut parents, b));
+            parents[root_a] = root_b;
+        }
+    }
+
+    // Each cell takes its hyperplane from its largest facet.
+    let mut cells: Vec<HullCell> = Vec::new();
+    let mut cell_ids = HashMap::new();
+    let mut cell_sizes = Vec::new();
+    let mut facet_cells = Vec::new();
+    for (facet_id, facet) in facets.iter().enumerate() {
+        let group = root(&mut parents, facet_id);
+        let cell_id = *cell_ids.entry(group).or_insert_with(|| {
+            cells.push(HullCell { normal: facet.normal, offset: facet.offset, verts: Vec::new() });
+            cell_sizes.push(0.);
+            cells.len() - 1
+        });
+        if facet.size > cell_sizes[cell_id] {
+            cells[cell_id].normal = facet.normal;
+            cells[cell_id].offset = facet.offset;
+            cell_sizes[cell_id] = facet.size;
+        }
+        facet_cells.push(cell_id);
+    }
+
+    // Points on the boundary of the hull, but not at its corners (eg in the middle
+    // of a cell, face or edge) can end up in the triangulation. A real vertex of a
+    // 4-polytope is pinned down by the cells it touches: their normals span all
+    // four dimensions. Counting the cells isn't enough, since four or more cells
+    // can meet at an edge.
+    let mut candidates: Vec<usize> = facets.iter().flat_map(|f| f.verts.to_vec()).collect();
+    candidates.sort();
+    candidates.dedup();
+
+    let mut verts = Vec::new();
+    for i in &candidates {
+        let on_cells: Vec<usize> = (0..cells.len())
+            .filter(|c| (dot(&cells[*c].normal, &points[*i]) - cells[*c].offset).abs() < eps)
+            .collect();
+        let normals: Vec<Vec4> = on_cells.iter().map(|c| cells[*c].normal).collect();
+        if rank(&normals, 1e-6) == 4 {
+            verts.push(*i);
+            for c in on_cells {
+                cells[c].verts.push(*i);
+            }
+        }
+    }
+
+    // Cells are neighbors if a ridge of the triangulation joins facets from both.
+    let mut neighbors = Vec::new();
+    for facet_ids in &ridges {
+        let (a, b) = (facet_cells[facet_ids[0]], facet_cells[facet_ids[1]]);
+        if a != b {
+            neighbors.push(if a < b { (a, b) } else { (b, a) });
+        }
+    }
+    neighbors.sort();
+    neighbors.dedup();
+
+    let mut faces = Vec::new();
+    for (a, b) in neighbors {
+        let mut face_verts: Vec<usize> = cells[a].verts.iter()
+            .filter(|v| cells[b].verts.contains(v))
+            .cloned()
+            .collect();
+
+        // Cells that only touch at an edge or vertex don't share a face.
+        if face_verts.len() < 3 { continue }
+
+        order_polygon(points, &mut face_verts);
+        faces.push(HullFace { verts: face_verts, cells: (a, b) });
+    }
+
+    Ok(Hull { verts, cells, faces })
+}
@@ -23,7 +23,7 @@ pub enum MoveDirection{
     Kata,
 }
 
-pub fn move_camera(direction: MoveDirection, θ: &Array1<f32>, amount: f32) -> Array1<f32> {
+pub fn move_camera(direction: MoveDirection, cam: &Camera, amount: f32) -> Array1<f32> {
     // Move the camera to a new position, based on where it's pointing.
     let unit_vec = match direction {
         MoveDirection::Forward => array![0., 0., 1., 0.],
@@ -37,19 +37,33 @@ pub fn move_camera(direction: MoveDirection, θ: &Array1<f32>, amount: f32) -> A
     };
 
 //    unit_vec * amount
-    let adjusted_θ = array![θ[0], θ[1], θ[2], 0., 0., 0.];
-
-    let v = transforms::dot_mv4(transforms::make_rotator4(&adjusted_θ),
-                                [unit_vec[0], unit_vec[1], unit_vec[2], unit_vec[3]]).to_vec();
+    let unit_vec = [unit_vec[0], unit_vec[1], unit_vec[2], unit_vec[3]];
+    let v = match cam.rotor {
+        Some(rotor) => rotor.rotate(unit_vec).to_vec(),
+        None => {
+            let θ = &cam.θ;
+            let adjusted_θ = array![θ[0], θ[1], θ[2], 0., 0., 0.];
+            transforms::dot_mv4(transforms::make_rotator4(&adjusted_θ), unit_vec).to_vec()
+        }
+    };
     Array::from_vec(v) * amount
 
 }
 
-pub fn handle_pressed<'a>(pressed: &[u32], delta_time: f32,
+pub fn handle_pressed<'a>(pressed: &[u32], newly_pressed: &[u32], delta_time: f32,
                       scene: &'a mut Scene,
                       scene_lib: &'a HashMap<u32, Scene>) -> () {
     // shape is only used when displaying single shapes.
-    // delta_time is in seconds.
+    // delta_time is in seconds. pressed is every key that's down; newly_pressed
+    // only those pressed since the last frame, for toggles.
+    for code in newly_pressed {
+        match *code {
+            15 => scene.wireframe = !scene.wireframe,  // Tab
+            25 => scene.cam.projection = scene.cam.projection.next(),  // P
+            _ => (),
+        }
+    }
+
     let move_amount = scene.sensitivities.0 * delta_time;
     let rotate_amount = scene.sensitivities.1 * delta_time;
     let zoom_amount = scene.sensitivities.2 * delta_time;
@@ -62,58 +76,58 @@ pub fn handle_pressed<'a>(pressed: &[u32], delta_time: f32,
             17 => {  // W
                 match scene.cam_type {
                     CameraType::Single => (),
-                    _ => scene.cam.position += &move_camera(MoveDirection::Forward, &scene.cam.θ, move_amount)
+                    _ => scene.cam.position += &move_camera(MoveDirection::Forward, &scene.cam, move_amount)
                 }
             },
             31 => {  // S
                 match scene.cam_type {
                     CameraType::Single => (),
-                    _ => scene.cam.position += &move_camera(MoveDirection::Back, &scene.cam.θ, move_amount)
+                    _ => scene.cam.position += &move_camera(MoveDirection::Back, &scene.cam, move_amount)
                 }
             },
             30 => {  // A
                 match scene.cam_type {
                     CameraType::Single => (),
-                    _ => scene.cam.position += &move_camera(MoveDirection::Left, &scene.cam.θ, move_amount)
+                    _ => scene.cam.position += &move_camera(MoveDirection::Left, &scene.cam, move_amount)
                 }
             },
             32 => {  // D
                 match scene.cam_type {
                     CameraType::Single => (),
-                    _ => scene.cam.position += &move_camera(MoveDirection::Right, &scene.cam.θ, move_amount)
+                    _ => scene.cam.position += &move_camera(MoveDirection::Right, &scene.cam, move_amount)
                 }
             },
             46 => {  // C
                 match scene.cam_type {
                     CameraType::Single => (),
                     CameraType::FPS => (),
-                    _ => scene.cam.position += &move_camera(MoveDirection::Down, &scene.cam.θ, move_amount)
+                    _ => scene.cam.position += &move_camera(MoveDirection::Down, &scene.cam, move_amount)
                 }
             },
             29 => {  // Lctrl
                 match scene.cam_type {
                     CameraType::Single => (),
                     CameraType::FPS => (),
-                    _ => scene.cam.position += &move_camera(MoveDirection::Down, &scene.cam.θ, move_amount)
+                    _ => scene.cam.position += &move_camera(MoveDirection::Down, &scene.cam, move_amount)
                 }
             },
             57 => {  // Space
                 match scene.cam_type {
                     CameraType::Single => (),
                     CameraType::FPS => (),
-                    _ => scene.cam.position += &move_camera(MoveDirection::Up, &scene.cam.θ, move_amount)
+                    _ => scene.cam.position += &move_camera(MoveDirection::Up, &scene.cam, move_amount)
                 }
             },
             33 => {  // F
                 match scene.cam_type {
                     CameraType::Single => (),
-                    _ => scene.cam.position += &move_camera(MoveDirection::Kata, &scene.cam.θ, move_amount)
+                    _ => scene.cam.position += &move_camera(MoveDirection::Kata, &scene.cam, move_amount)
                 }
             },
             19 => {  // R
                 match scene.cam_type {
                     CameraType::Single => (),
-                    _ => scene.cam.position += &move_camera(MoveDirection::Ana, &scene.cam.θ, move_amount)
+                    _ => scene.cam.position += &move_camera(MoveDirection::Ana, &scene.cam, move_amount)
                 }
             },
 
@@ -121,77 +135,77 @@ pub fn handle_pressed<'a>(pressed: &[u32], delta_time: f32,
             // X rotations range from -τ/4 to τ/4 (Looking straight down to up)
             75 => {  // Left
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[2] -= rotate_amount,
-                    _ => scene.cam.θ[2] -= rotate_amount
+                    CameraType::Single => shape.turn(2, -rotate_amount),
+                    _ => scene.cam.turn(2, -rotate_amount)
                 }
             },
             77 => {  // Right
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[2] += rotate_amount,
-                    _ => scene.cam.θ[2] += rotate_amount
+                    CameraType::Single => shape.turn(2, rotate_amount),
+                    _ => scene.cam.turn(2, rotate_amount)
                 }
             },
             // Don't allow us to look greater than τ/4 up or down.
             80 => {  // Down
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[1] -= rotate_amount,
-                    _ => scene.cam.θ[1] -= rotate_amount
+                    CameraType::Single => shape.turn(1, -rotate_amount),
+                    _ => scene.cam.turn(1, -rotate_amount)
                 }
             },
             72 => {  // Up
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[1] += rotate_amount,
-                    _ => scene.cam.θ[1] += rotate_amount
+                    CameraType::Single => shape.turn(1, rotate_amount),
+                    _ => scene.cam.turn(1, rotate_amount)
                 }
             },
             16 => {  // Q
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[0] -= rotate_amount,
-                    _ => scene.cam.θ[0] -= rotate_amount
+                    CameraType::Single => shape.turn(0, -rotate_amount),
+                    _ => scene.cam.turn(0, -rotate_amount)
                 }
             },
             18 => {  // E
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[0] += rotate_amount,
-                    _ => scene.cam.θ[0] += rotate_amount
+                    CameraType::Single => shape.turn(0, rotate_amount),
+                    _ => scene.cam.turn(0, rotate_amount)
                 }
             },
 
             // 4d rotations
             82 => {  // Ins
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[3] += rotate_amount,
-                    _ => scene.cam.θ[3] += rotate_amount
+                    CameraType::Single => shape.turn(3, rotate_amount),
+                    _ => scene.cam.turn(3, rotate_amount)
                 }
             },
             83 => {  // Del
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[3] -= rotate_amount,
-                    _ => scene.cam.θ[3] -= rotate_amount
+                    CameraType::Single => shape.turn(3, -rotate_amount),
+                    _ => scene.cam.turn(3, -rotate_amount)
                 }
             },
             71 => {  // Home
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[4] += rotate_amount,
-                    _ => scene.cam.θ[4] += rotate_amount
+                    CameraType::Single => shape.turn(4, rotate_amount),
+                    _ => scene.cam.turn(4, rotate_amount)
                 }
             },
             79 => {  // End
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[4] -= rotate_amount,
-                    _ => scene.cam.θ[4] -= rotate_amount
+                    CameraType::Single => shape.turn(4, -rotate_amount),
+                    _ => scene.cam.turn(4, -rotate_amount)
                 }
             },
             73 => {  // Pgup
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[5] += rotate_amount,
-                    _ => scene.cam.θ[5] += rotate_amount
+                    CameraType::Single => shape.turn(5, rotate_amount),
+                    _ => scene.cam.turn(5, rotate_amount)
                 }
             },
             81 => {  // Pgdn
                 match scene.cam_type {
-                    CameraType::Single => shape.orientation[5] -= rotate_amount,
-                    _ => scene.cam.θ[5] -= rotate_amount
+                    CameraType::Single => shape.turn(5, -rotate_amount),
+                    _ => scene.cam.turn(5, -rotate_amount)
                 }
             },
             // Zoom
@@ -25,6 +25,8 @@ extern crate wasm_bindgen;
 #[macro_use]
 extern crate serde_derive;
 
+mod hull;
+mod rotor;
 mod util;
 mod scenes;
 mod shape_maker;
@@ -52,6 +54,15 @@ pub fn scene_lib() -> JsValue {
     scene_lib.insert(0, scenes::hypercube_scene(aspect));
     scene_lib.insert(1, scenes::fivecell_scene(aspect));
     scene_lib.insert(10, scenes::twentyfourcell_scene(aspect));
+    scene_lib.insert(11, scenes::sixhundredcell_scene(aspect));
+    scene_lib.insert(12, scenes::onehundredtwentycell_scene(aspect));
+    scene_lib.insert(13, scenes::sixteencell_scene(aspect));
+    scene_lib.insert(14, scenes::spherecone_scene(aspect));
+    scene_lib.insert(15, scenes::cubecone_scene(aspect));
+    scene_lib.insert(16, scenes::duoprism_scene(aspect));
+    scene_lib.insert(17, scenes::duocylinder_scene(aspect));
+    scene_lib.insert(18, scenes::glome_scene(aspect));
+    scene_lib.insert(19, scenes::clifford_torus_scene(aspect));
     scene_lib.insert(2, scenes::spherinder_scene(aspect));
     scene_lib.insert(3, scenes::cube_scene(aspect));
     scene_lib.insert(4, scenes::pyramid_scene(aspect));
@@ -80,22 +91,23 @@ fn mat_as_js(mat: [[f32; 4]; 4]) -> Vec<f32> {
 #[wasm_bindgen]
 pub fn view_mat(θ: Vec<f32>) -> Vec<f32> {
     let mat = transforms::make_view_mat4(&Array::from_vec(θ));
-    mat_as_js(mat)
+    mat_as_js(transforms::to_uniform_mat4(mat))
 }
 
 #[wasm_bindgen]
 pub fn model_mat(orientation: Vec<f32>, scale: f32) -> Vec<f32> {
     let mat = transforms::make_model_mat4(&Array::from_vec(orientation), scale);
-    mat_as_js(mat)
+    mat_as_js(transforms::to_uniform_mat4(mat))
 }
 
 #[wasm_bindgen]
 pub fn proj_mat(position: Vec<f32>, θ: Vec<f32>, fov: f32, aspect: f32, aspect_4: f32,
-                 near: f32, far: f32, fourd_proj_dist: f32) -> Vec<f32> {
+                 near: f32, far: f32) -> Vec<f32> {
     // We can't pass the camera directly due to bindgen limitations.
     let cam = Camera {
         position: Array::from_vec(position), θ: Array::from_vec(θ),
-        fov, aspect, aspect_4, near, far, fourd_proj_dist
+        fov, aspect, aspect_4, near, far,
+        projection: transforms::Projection4::Orthographic, rotor: None
     };
 
     let mat = transforms::make_proj_mat_gl(&cam);
@@ -30,7 +30,9 @@ extern crate vulkano_win;
 extern crate wasm_bindgen;
 extern crate winit;
 
+mod hull;
 mod input;
+mod rotor;
 mod scenes;
 mod shape_maker;
 mod types;
@@ -37,8 +37,9 @@ use winit;
 use input;
 use scenes;
 use shape_maker;
+use rotor::Bivector4;
 use transforms;
-use types::{Camera, Shape, Vertex, VertAndExtras, Normal};
+use types::{Camera, MeshError, Shape, Vertex, VertAndExtras, Normal};
 
 const WIDTH: u32 = 1024;
 const HEIGHT: u32 = 768;
@@ -61,50 +62,56 @@ mod fs {
 
 // impl_vertex here, so we don't have to use the vulkano crate in wasm.
 impl_vertex!(Vertex, position);
-impl_vertex!(VertAndExtras, position, shape_posit, normal, specular_intensity);
+impl_vertex!(VertAndExtras, position, normal, specular_intensity);
 impl_vertex!(Normal, normal);
 
 pub fn make_static_buffers(shapes: &HashMap<u32, Shape>, device: Arc<device::Device>) ->
-        (HashMap<u32, Arc<CpuAccessibleBuffer<[u32]>>>, HashMap<u32, Arc<CpuAccessibleBuffer<[VertAndExtras]>>>) {
-    // Make index and vertex buffers.
+        (HashMap<u32, Arc<CpuAccessibleBuffer<[u32]>>>, HashMap<u32, Arc<CpuAccessibleBuffer<[u32]>>>,
+         HashMap<u32, Arc<CpuAccessibleBuffer<[VertAndExtras]>>>, HashMap<u32, MeshError>) {
+    // Make index and vertex buffers. There are two index buffers per shape: triangles
+    // for drawing faces, and line pairs for drawing edges. Vertices are shared between
+    // faces wherever their normals allow; see Mesh::to_indexed. Shapes whose meshes
+    // can't be laid out get no buffers; their errors are returned by shape id, so the
+    // rest of the scene can still be drawn.
     let mut index_buffers = HashMap::new();
+    let mut edge_index_buffers = HashMap::new();
     let mut vertex_buffers = HashMap::new();
+    let mut mesh_errors = HashMap::new();
 
     for (s_id, shape) in shapes {
-        let mut indices = Vec::new();
-        let mut vertex_info = Vec::new();
-        let mut index_modifier = 0;
-
-        let mut tri_indices: Vec<u32> = shape.mesh.tris.iter().map(|ind| ind + index_modifier).collect();
-        indices.append(&mut tri_indices);
-        index_modifier += shape.mesh.num_face_verts();
-
-        for (i, face) in shape.mesh.faces_vert.iter().enumerate() {
-            for vert_id in face {
-                let info = VertAndExtras {
-                    position: shape.mesh.vertices[vert_id].position,
-                    shape_posit: (shape.position[0], shape.position[1],
-                                  shape.position[2], shape.position[3]),
-                    normal: shape.mesh.normals[i].normal,
-                    specular_intensity: shape.specular_intensity,
-                };
-                vertex_info.push(info);
+        let indexed = match shape.mesh.to_indexed(shape.smooth_normals) {
+            Ok(indexed) => indexed,
+            Err(e) => {
+                mesh_errors.insert(*s_id, e);
+                continue
             }
-        }
+        };
+
+        let vertex_info: Vec<VertAndExtras> = indexed.vertices.iter().zip(&indexed.normals)
+            .map(|(vert, norm)| VertAndExtras {
+                position: vert.position,
+                normal: norm.normal,
+                specular_intensity: shape.specular_intensity,
+            }).collect();
 
         let index_buffer = CpuAccessibleBuffer::from_iter(device.clone(), buffer::BufferUsage::all(),
-                                                           indices.iter().cloned())
+                                                           indexed.tris.iter().cloned())
             .expect("Failed to create index buffer");
 
+        let edge_index_buffer = CpuAccessibleBuffer::from_iter(device.clone(), buffer::BufferUsage::all(),
+                                                                indexed.edges.iter().cloned())
+            .expect("Failed to create edge index buffer");
+
         let vertex_buffer = CpuAccessibleBuffer::from_iter(
             device.clone(), buffer::BufferUsage::all(), vertex_info.iter().cloned())
             .expect("failed to create vertex buffer");
 
         index_buffers.insert(*s_id, index_buffer);
+        edge_index_buffers.insert(*s_id, edge_index_buffer);
         vertex_buffers.insert(*s_id, vertex_buffer);
     }
 
-    (index_buffers, vertex_buffers)
+    (index_buffers, edge_index_buffers, vertex_buffers, mesh_errors)
 }
 
 pub fn render() {
@@ -117,6 +124,15 @@ pub fn render() {
     scene_lib.insert(0, scenes::hypercube_scene(aspect));
     scene_lib.insert(1, scenes::fivecell_scene(aspect));
     scene_lib.insert(10, scenes::twentyfourcell_scene(aspect));
+    scene_lib.insert(11, scenes::sixhundredcell_scene(aspect));
+    scene_lib.insert(12, scenes::onehundredtwentycell_scene(aspect));
+    scene_lib.insert(13, scenes::sixteencell_scene(aspect));
+    scene_lib.insert(14, scenes::spherecone_scene(aspect));
+    scene_lib.insert(15, scenes::cubecone_scene(aspect));
+    scene_lib.insert(16, scenes::duoprism_scene(aspect));
+    scene_lib.insert(17, scenes::duocylinder_scene(aspect));
+    scene_lib.insert(18, scenes::glome_scene(aspect));
+    scene_lib.insert(19, scenes::clifford_torus_scene(aspect));
     scene_lib.insert(2, scenes::spherinder_scene(aspect));
     scene_lib.insert(3, scenes::cube_scene(aspect));
     scene_lib.insert(4, scenes::pyramid_scene(aspect));
@@ -129,7 +145,7 @@ pub fn render() {
     let mut scene = scene_lib[&10].clone();
 
     let mut currently_pressed: Vec<u32> = Vec::new();
-    let mut currently_pressed: Vec<u32> = Vec::new();
+    let mut newly_pressed: Vec<u32> = Vec::new();
 
     // The first step of any vulkan program is to create an instance.
     let instance = {
@@ -263,7 +279,11 @@ pub fn render() {
     let depth_buffer = image::attachment::AttachmentImage::transient(
         device_.clone(), dimensions, format::D16Unorm).unwrap();
 
-    let (index_buffers, vertex_buffers) = make_static_buffers(&scene.shapes, device_.clone());
+    let (index_buffers, edge_index_buffers, vertex_buffers, mesh_errors) =
+        make_static_buffers(&scene.shapes, device_.clone());
+    for (shape_id, e) in &mesh_errors {
+        println!("Not drawing shape {}: {}", shape_id, e);
+    }
 
     // todo move depth_buffer and unifform buffer to one of the make_buffer funcs.
 
@@ -354,6 +374,21 @@ pub fn render() {
         .build(device_.clone())
         .unwrap());
 
+    // Wireframe mode draws each pair of indices as a line, with the same shaders.
+    let wireframe_pipeline = Arc::new(pipeline::GraphicsPipeline::start()
+        .vertex_input_single_buffer()
+        .vertex_shader(vs.main_entry_point(), ())
+        .line_list()
+        .viewports_dynamic_scissors_irrelevant(1)
+        .cull_mode_disabled()
+        .sample_shading_disabled()
+        .blend_alpha_blending()
+        .fragment_shader(fs.main_entry_point(), ())
+        .depth_stencil_disabled()
+        .render_pass(framebuffer::Subpass::from(render_pass.clone(), 0).unwrap())
+        .build(device_.clone())
+        .unwrap());
+
     // The render pass we created above only describes the layout of our framebuffers. Before we
     // can draw we also need to create the actual framebuffers.
     //
@@ -384,10 +419,14 @@ pub fn render() {
 
     let mut prev_frame_start = time::Instant::now();
 
+    let (projection_mode, projection_param) = scene.cam.projection.to_uniforms();
     let mut static_uniforms = vs::ty::Data {
         // view matrix will change per frame; model will change per shape.
         model: transforms::I4(),
+        model_translation: [0.; 4],
+        normal_mat: transforms::I4(),
         view: transforms::I4(),
+        view_translation: [0.; 4],
         proj,
         cam_position: [scene.cam.position[0], scene.cam.position[1],
             scene.cam.position[2], scene.cam.position[3]],
@@ -400,6 +439,8 @@ pub fn render() {
         diffuse_intensity: scene.lighting.diffuse_intensity,
         color_max: scene.color_max,
         shape_opacity: 0.,
+        projection_mode,
+        projection_param,
 
 //        light_sources: scene.lighting.sources,
     };
@@ -491,18 +532,31 @@ pub fn render() {
                 ]
             ).unwrap();
 
-        let view_mat = transforms::make_view_mat4(&scene.cam.θ);
+        let view = transforms::make_view_transform(&scene.cam);
+        let (projection_mode, projection_param) = scene.cam.projection.to_uniforms();
         let static_uniforms_perframe = vs::ty::Data {
-            view: view_mat,
+            view: transforms::to_uniform_mat4(view.linear),
+            view_translation: view.translation,
+            projection_mode,
+            projection_param,
             cam_position: [scene.cam.position[0], scene.cam.position[1], scene.cam.position[2], scene.cam.position[3]],
             ..static_uniforms
         };
         // Update the view matrix once per frame.
 
         for (shape_id, shape) in &scene.shapes {
+            // Shapes make_static_buffers couldn't lay out have no buffers.
+            let vertex_buffer = match vertex_buffers.get(shape_id) {
+                Some(buffer) => buffer.clone(),
+                None => continue,
+            };
+
             let uniform_buffer_subbuffer = {
+                let model = transforms::make_model_transform(shape);
                 let uniform_data = vs::ty::Data {
-                    model: transforms::make_model_mat4(&shape.orientation, shape.scale),
+                    model: transforms::to_uniform_mat4(model.linear),
+                    model_translation: model.translation,
+                    normal_mat: transforms::to_uniform_mat4(transforms::make_normal_mat4(model.linear)),
                     shape_opacity: shape.opacity,
                     ..static_uniforms_perframe
                 };
@@ -510,7 +564,13 @@ pub fn render() {
                 uniform_buffer.next(uniform_data).unwrap()
             };
 
-            let set = Arc::new(descriptor::descriptor_set::PersistentDescriptorSet::start(pipeline_.clone(), 0)
+            let (pipeline, indices) = if scene.wireframe {
+                (wireframe_pipeline.clone(), edge_index_buffers[shape_id].clone())
+            } else {
+                (pipeline_.clone(), index_buffers[shape_id].clone())
+            };
+
+            let set = Arc::new(descriptor::descriptor_set::PersistentDescriptorSet::start(pipeline.clone(), 0)
                 .add_buffer(uniform_buffer_subbuffer).unwrap()
                 .build().unwrap()
             );
@@ -521,7 +581,7 @@ pub fn render() {
             // Since we used an `EmptyPipeline` object, the objects have to be `()`.
 
             command_buffer_ = command_buffer_.draw_indexed(
-                pipeline_.clone(),
+                pipeline,
                 command_buffer::DynamicState {
                     line_width: None,
                     viewports: Some(vec![pipeline::viewport::Viewport {
@@ -531,8 +591,8 @@ pub fn render() {
                     }]),
                     scissors: None,
                 },
-                vertex_buffers[shape_id].clone(),
-                index_buffers[shape_id].clone(), set, ()
+                vertex_buffer,
+                indices, set, ()
             ).unwrap();
         }
 
@@ -582,7 +642,11 @@ pub fn render() {
 
         // Rotate scene.shapes.
         for (id, shape) in &mut scene.shapes {
-            shape.orientation += &(&shape.rotation_speed * delta_time);
+            match shape.rotor {
+                Some(rotor) => shape.rotor = Some(rotor.integrate(
+                    &Bivector4::from_angles(&shape.rotation_speed), delta_time)),
+                None => shape.orientation += &(&shape.rotation_speed * delta_time),
+            }
         }
 
         // Note that in more complex programs it is likely that one of `acquire_next_image`,
@@ -615,7 +679,10 @@ pub fn render() {
                     }, ..
                 } => match el_state {
                     winit::ElementState::Pressed => {
-                        if !currently_pressed.contains(&code) { currently_pressed.push(code) }
+                        if !currently_pressed.contains(&code) {
+                            currently_pressed.push(code);
+                            newly_pressed.push(code);
+                        }
                     },
                     winit::ElementState::Released => {
                         currently_pressed.remove_item(&code);
@@ -626,6 +693,7 @@ pub fn render() {
         });
         if done { return; }
 
-        input::handle_pressed(&currently_pressed, delta_time, &mut scene, &scene_lib);
+        input::handle_pressed(&currently_pressed, &newly_pressed, delta_time, &mut scene, &scene_lib);
+        newly_pressed.clear();
     }
 }
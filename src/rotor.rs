@@ -0,0 +1,529 @@
+// Rotors: an alternative to orientation angles for 4d rotations. A rotor is an
+// element of the even subalgebra of the geometric algebra of 4d space, and rotates
+// a vector v to R v R~. Unlike the six angles used by make_rotator4, rotors compose
+// without depending on the order of the planes, and a bivector of angular velocity
+// integrates to a constant-velocity rotation.
+
+use std::ops::Mul;
+
+use ndarray::prelude::*;
+
+// Multivectors are stored by blade, with bit 0 of the index standing for x, bit 1
+// for y, bit 2 for z and bit 3 for u. Eg index 0b0101 is the xz blade.
+type Multivector = [f32; 16];
+
+const XY: usize = 0b0011;
+const XZ: usize = 0b0101;
+const XU: usize = 0b1001;
+const YZ: usize = 0b0110;
+const YU: usize = 0b1010;
+const ZU: usize = 0b1100;
+const XYZU: usize = 0b1111;
+
+fn blade_sign(a: usize, b: usize) -> f32 {
+    // The sign from reordering the product of two basis blades into canonical
+    // order. Each basis vector squares to 1.
+    let mut swaps = 0;
+    let mut a = a >> 1;
+    while a != 0 {
+        swaps += (a & b).count_ones();
+        a >>= 1;
+    }
+    if swaps % 2 == 0 { 1. } else { -1. }
+}
+
+fn product(a: &Multivector, b: &Multivector) -> Multivector {
+    // The geometric product.
+    let mut result = [0.; 16];
+    for i in 0..16 {
+        if a[i] == 0. { continue }
+        for j in 0..16 {
+            if b[j] == 0. { continue }
+            result[i ^ j] += blade_sign(i, j) * a[i] * b[j];
+        }
+    }
+    result
+}
+
+fn vector(v: [f32; 4]) -> Multivector {
+    let mut result = [0.; 16];
+    for i in 0..4 {
+        result[1 << i] = v[i];
+    }
+    result
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bivector4 {
+    // Angles (or angular velocities) of rotation in each plane, turning the plane's
+    // first axis towards its second.
+    pub xy: f32,
+    pub xz: f32,
+    pub xu: f32,
+    pub yz: f32,
+    pub yu: f32,
+    pub zu: f32,
+}
+
+impl Bivector4 {
+    pub fn new(xy: f32, xz: f32, xu: f32, yz: f32, yu: f32, zu: f32) -> Bivector4 {
+        Bivector4 { xy, xz, xu, yz, yu, zu }
+    }
+
+    pub fn from_angles(θ: &Array1<f32>) -> Bivector4 {
+        // Convert from the six-angle order and signs make_rotator4 uses; eg a
+        // shape's rotation_speed. Each angle alone gives the same rotation either way.
+        Bivector4::new(-θ[0], θ[2], -θ[3], -θ[1], θ[4], θ[5])
+    }
+
+    pub fn scale(&self, s: f32) -> Bivector4 {
+        Bivector4::new(self.xy * s, self.xz * s, self.xu * s, self.yz * s, self.yu * s, self.zu * s)
+    }
+
+    fn to_multivector(&self) -> Multivector {
+        let mut result = [0.; 16];
+        result[XY] = self.xy;
+        result[XZ] = self.xz;
+        result[XU] = self.xu;
+        result[YZ] = self.yz;
+        result[YU] = self.yu;
+        result[ZU] = self.zu;
+        result
+    }
+
+    pub fn exp(&self) -> Rotor4 {
+        // The rotor rotating by these angles; for a bivector of angular velocity
+        // times a duration, the rotation over that time. In the algebra it's exp(-B/2).
+        //
+        // b = -self/2 splits into parts that commute: b P+ and b P-, with
+        // P± = (1 ± I)/2, where I is the xyzu pseudoscalar. b² = -α + βI, and on
+        // each part I is ±1, so (b P±)² = -(α ∓ β) P±: each part exponentiates
+        // like a complex number.
+        let b = self.scale(-0.5).to_multivector();
+        let b_squared = product(&b, &b);
+        let (α, β) = (-b_squared[0], b_squared[XYZU]);
+
+        let mut pseudoscalar = [0.; 16];
+        pseudoscalar[XYZU] = 1.;
+        let b_pseudo = product(&b, &pseudoscalar);
+
+        let mut result = [0.; 16];
+        for &(sign, c2) in &[(1., α - β), (-1., α + β)] {
+            let c = c2.max(0.).sqrt();
+            let k = if c < 1e-6 { 1. } else { c.sin() / c };
+            // P± cos c + k b P±
+            result[0] += c.cos() / 2.;
+            result[XYZU] += sign * c.cos() / 2.;
+            for i in 0..16 {
+                result[i] += k * (b[i] + sign * b_pseudo[i]) / 2.;
+            }
+        }
+        Rotor4::from_multivector(&result)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rotor4 {
+    // A scalar, the six bivector planes, and the pseudoscalar.
+    pub s: f32,
+    pub xy: f32,
+    pub xz: f32,
+    pub xu: f32,
+    pub yz: f32,
+    pub yu: f32,
+    pub zu: f32,
+    pub xyzu: f32,
+}
+
+impl Rotor4 {
+    pub fn identity() -> Rotor4 {
+        Rotor4 { s: 1., xy: 0., xz: 0., xu: 0., yz: 0., yu: 0., zu: 0., xyzu: 0. }
+    }
+
+    pub fn from_plane(plane: (usize, usize), angle: f32) -> Rotor4 {
+        // A rotation by angle in one plane, turning axis plane.0 towards plane.1;
+        // axes are numbered x, y, z, u from 0.
+        let mut result = [0.; 16];
+        result[0] = (angle / 2.).cos();
+        let sign = if plane.0 < plane.1 { 1. } else { -1. };
+        result[(1 << plane.0) | (1 << plane.1)] = -sign * (angle / 2.).sin();
+        Rotor4::from_multivector(&result)
+    }
+
+    pub fn from_angles(θ: &Array1<f32>) -> Rotor4 {
+        // The rotor for six orientation angles, matching make_rotator4: it applies
+        // the planes in the order zu, yu, xu, xz, yz, xy.
+        Rotor4::from_plane((0, 1), -θ[0]) * Rotor4::from_plane((1, 2), -θ[1]) *
+            Rotor4::from_plane((0, 2), θ[2]) * Rotor4::from_plane((0, 3), -θ[3]) *
+            Rotor4::from_plane((1, 3), θ[4]) * Rotor4::from_plane((2, 3), θ[5])
+    }
+
+    fn to_multivector(&self) -> Multivector {
+        let mut result = [0.; 16];
+        result[0] = self.s;
+        result[XY] = self.xy;
+        result[XZ] = self.xz;
+        result[XU] = self.xu;
+        result[YZ] = self.yz;
+        result[YU] = self.yu;
+        result[ZU] = self.zu;
+        result[XYZU] = self.xyzu;
+        result
+    }
+
+    fn from_multivector(m: &Multivector) -> Rotor4 {
+        // Odd-grade parts are dropped; they're zero for products of rotors.
+        Rotor4 { s: m[0], xy: m[XY], xz: m[XZ], xu: m[XU], yz: m[YZ], yu: m[YU], zu: m[ZU],
+            xyzu: m[XYZU] }
+    }
+
+    pub fn reverse(&self) -> Rotor4 {
+        // The inverse rotation, for a normalized rotor.
+        Rotor4 { s: self.s, xy: -self.xy, xz: -self.xz, xu: -self.xu, yz: -self.yz, yu: -self.yu,
+            zu: -self.zu, xyzu: self.xyzu }
+    }
+
+    pub fn normalize(&self) -> Rotor4 {
+        // Correct floating-point drift, eg after many integration steps.
+        let norm = (self.s.powi(2) + self.xy.powi(2) + self.xz.powi(2) + self.xu.powi(2) +
+            self.yz.powi(2) + self.yu.powi(2) + self.zu.powi(2) + self.xyzu.powi(2)).sqrt();
+        let m: Vec<f32> = self.to_multivector().iter().map(|c| c / norm).collect();
+        let mut result = [0.; 16];
+        result.copy_from_slice(&m);
+        Rotor4::from_multivector(&result)
+    }
+
+    pub fn rotate(&self, v: [f32; 4]) -> [f32; 4] {
+        let m = self.to_multivector();
+        let rotated = product(&product(&m, &vector(v)), &self.reverse().to_multivector());
+        [rotated[1], rotated[2], rotated[4], rotated[8]]
+    }
+
+    pub fn to_matrix(&self) -> [[f32; 4]; 4] {
+        // In the same row-major layout as make_rotator4; column j is where the jth
+        // axis ends up.
+        let mut result = [[0.; 4]; 4];
+        for j in 0..4 {
+            let mut axis = [0.; 4];
+            axis[j] = 1.;
+            let col = self.rotate(axis);
+            for i in 0..4 {
+                result[i][j] = col[i];
+            }
+        }
+        result
+    }
+
+    pub fn from_matrix(m: [[f32; 4]; 4]) -> Rotor4 {
+        // The rotor for a rotation matrix; of the two rotors for each rotation, R
+        // and -R, either may be returned.
+        //
+        // With f the rotation applied to each blade, the sum over all blades a of
+        // f(e_a) x e_a⁻¹ is 16 <R~ x> R. Try x = each even blade, keeping the
+        // largest result: at least one of R's components is large.
+        let mut images = [[0.; 16]; 16];
+        for a in 0..16 {
+            // f(e_a) is the product of the images of a's axes, which stay orthogonal.
+            let mut image = [0.; 16];
+            image[0] = 1.;
+            for i in 0..4 {
+                if a & (1 << i) != 0 {
+                    image = product(&image, &vector([m[0][i], m[1][i], m[2][i], m[3][i]]));
+                }
+            }
+            images[a] = image;
+        }
+
+        let mut best = ([0.; 16], 0.);
+        for &x in &[0, XY, XZ, XU, YZ, YU, ZU, XYZU] {
+            let mut sum = [0.; 16];
+            for a in 0..16 {
+                let mut blade = [0.; 16];
+                blade[x] = 1.;
+                let mut inverse = [0.; 16];
+                inverse[a] = blade_sign(a, a);  // e_a⁻¹ = e_a / (e_a)².
+                let term = product(&product(&images[a], &blade), &inverse);
+                for i in 0..16 {
+                    sum[i] += term[i];
+                }
+            }
+            let size = sum.iter().fold(0., |acc, c| acc + c * c);
+            if size > best.1 {
+                best = (sum, size);
+            }
+        }
+        Rotor4::from_multivector(&best.0).normalize()
+    }
+
+    pub fn isoclinic(&self) -> (Quaternion, Quaternion) {
+        // Split the rotation into left and right isoclinic rotations: unit
+        // quaternions l and r with p -> l p r, treating points as quaternions with x
+        // as the real part. (l, r) and (-l, -r) give the same rotation.
+        //
+        // The rotation's matrix is the sum over a and b of l_a r_b times the matrix
+        // of p -> e_a p e_b. Those are orthogonal, with squared length 4, so we can
+        // read off each l_a r_b, then factor.
+        let m = self.to_matrix();
+        let mut products = [[0.; 4]; 4];
+        for a in 0..4 {
+            for b in 0..4 {
+                let basis = isoclinic_basis(a, b);
+                products[a][b] = (0..16).map(|k| m[k / 4][k % 4] * basis[k / 4][k % 4]).sum::<f32>() / 4.;
+            }
+        }
+
+        // Each row of products is a multiple of r; the largest is the most accurate.
+        let row = (0..4).fold(0, |best, a| {
+            let len = |a: usize| products[a].iter().map(|c| c * c).sum::<f32>();
+            if len(a) > len(best) { a } else { best }
+        });
+        let r = Quaternion::from_array(products[row]);
+        let r = r.scale(1. / r.dot(&r).sqrt());
+        let l = Quaternion::from_array([
+            Quaternion::from_array(products[0]).dot(&r), Quaternion::from_array(products[1]).dot(&r),
+            Quaternion::from_array(products[2]).dot(&r), Quaternion::from_array(products[3]).dot(&r),
+        ]);
+        (l, r)
+    }
+
+    pub fn from_isoclinic(l: &Quaternion, r: &Quaternion) -> Rotor4 {
+        let mut m = [[0.; 4]; 4];
+        let (l, r) = (l.to_array(), r.to_array());
+        for a in 0..4 {
+            for b in 0..4 {
+                let basis = isoclinic_basis(a, b);
+                for k in 0..16 {
+                    m[k / 4][k % 4] += l[a] * r[b] * basis[k / 4][k % 4];
+                }
+            }
+        }
+        Rotor4::from_matrix(m)
+    }
+
+    pub fn slerp(&self, other: &Rotor4, t: f32) -> Rotor4 {
+        // Interpolate between orientations at a constant rate, along the shortest
+        // path: slerp the left and right isoclinic parts separately. Eg for camera
+        // transitions, or keyframed animation.
+        let (l0, r0) = self.isoclinic();
+        let (l1, r1) = other.isoclinic();
+
+        // Negating both of the target's quaternions gives the same rotation; pick
+        // whichever sign is closer.
+        let length = |sign: f32| {
+            let a = (sign * l0.dot(&l1)).max(-1.).min(1.).acos();
+            let b = (sign * r0.dot(&r1)).max(-1.).min(1.).acos();
+            a * a + b * b
+        };
+        let sign = if length(-1.) < length(1.) { -1. } else { 1. };
+
+        Rotor4::from_isoclinic(&l0.slerp(&l1.scale(sign), t), &r0.slerp(&r1.scale(sign), t))
+    }
+
+    pub fn integrate(&self, ω: &Bivector4, dt: f32) -> Rotor4 {
+        // Spin at angular velocity ω, in fixed (world) planes, for dt seconds.
+        (ω.scale(dt).exp() * *self).normalize()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Quaternion {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Quaternion {
+    pub fn new(w: f32, x: f32, y: f32, z: f32) -> Quaternion {
+        Quaternion { w, x, y, z }
+    }
+
+    fn from_array(a: [f32; 4]) -> Quaternion {
+        // 4d points map to quaternions with x as the real part.
+        Quaternion::new(a[0], a[1], a[2], a[3])
+    }
+
+    fn to_array(&self) -> [f32; 4] {
+        [self.w, self.x, self.y, self.z]
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f32 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn scale(&self, s: f32) -> Quaternion {
+        Quaternion::new(self.w * s, self.x * s, self.y * s, self.z * s)
+    }
+
+    pub fn slerp(&self, other: &Quaternion, t: f32) -> Quaternion {
+        // Along the great circle between two unit quaternions, without choosing
+        // between other and -other; callers handle that.
+        let d = self.dot(other).max(-1.).min(1.);
+        let angle = d.acos();
+        let (a, b) = if angle < 1e-5 {
+            (1. - t, t)
+        } else {
+            (((1. - t) * angle).sin() / angle.sin(), (t * angle).sin() / angle.sin())
+        };
+        let q = self.scale(a).to_array();
+        let r = other.scale(b).to_array();
+        let sum = Quaternion::new(q[0] + r[0], q[1] + r[1], q[2] + r[2], q[3] + r[3]);
+        sum.scale(1. / sum.dot(&sum).sqrt())
+    }
+}
+
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, o: Quaternion) -> Quaternion {
+        Quaternion::new(
+            self.w * o.w - self.x * o.x - self.y * o.y - self.z * o.z,
+            self.w * o.x + self.x * o.w + self.y * o.z - self.z * o.y,
+            self.w * o.y - self.x * o.z + self.y * o.w + self.z * o.x,
+            self.w * o.z + self.x * o.y - self.y * o.x + self.z * o.w,
+        )
+    }
+}
+
+fn isoclinic_basis(a: usize, b: usize) -> [[f32; 4]; 4] {
+    // The matrix of p -> e_a p e_b, with e_0 = 1, e_1 = i etc. These 16 matrices
+    // are orthogonal, so they're a basis for 4x4 matrices.
+    let unit = |k: usize| {
+        let mut result = [0.; 4];
+        result[k] = 1.;
+        Quaternion::from_array(result)
+    };
+    let (ea, eb) = (unit(a), unit(b));
+    let mut result = [[0.; 4]; 4];
+    for j in 0..4 {
+        let col = (ea * unit(j) * eb).to_array();
+        for i in 0..4 {
+            result[i][j] = col[i];
+        }
+    }
+    result
+}
+
+impl Mul for Rotor4 {
+    type Output = Rotor4;
+
+    fn mul(self, other: Rotor4) -> Rotor4 {
+        // Composition: apply other, then self, as with matrices.
+        Rotor4::from_multivector(&product(&self.to_multivector(), &other.to_multivector()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+    use transforms;
+
+    const τ: f32 = 2. * PI;
+
+    fn assert_mat_eq(a: [[f32; 4]; 4], b: [[f32; 4]; 4]) {
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!((a[i][j] - b[i][j]).abs() < 1e-5, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_angles() {
+        // Each plane alone, then all of them.
+        for k in 0..6 {
+            let mut θ = Array::zeros(6);
+            θ[k] = 0.7;
+            assert_mat_eq(Rotor4::from_angles(&θ).to_matrix(), transforms::make_rotator4(&θ));
+            assert_mat_eq(Bivector4::from_angles(&θ).exp().to_matrix(), transforms::make_rotator4(&θ));
+        }
+        let θ = array![0.3, -1.2, 2.5, 0.8, -0.4, 1.9];
+        assert_mat_eq(Rotor4::from_angles(&θ).to_matrix(), transforms::make_rotator4(&θ));
+    }
+
+    #[test]
+    fn test_from_matrix() {
+        // Including a half turn, whose rotor has no scalar part, and a double rotation.
+        for θ in &[array![0.3, -1.2, 2.5, 0.8, -0.4, 1.9], array![τ / 2., 0., 0., 0., 0., 0.],
+                   array![0., 0., 0., 0., 0., 0.], array![0., 0., τ / 2., 0., 0., τ / 2.]] {
+            let m = transforms::make_rotator4(θ);
+            assert_mat_eq(Rotor4::from_matrix(m).to_matrix(), m);
+        }
+    }
+
+    #[test]
+    fn test_exp() {
+        // A double rotation in two orthogonal planes, at different and equal rates.
+        for &(a, b) in &[(0.9, 0.4), (1.1, 1.1), (0.5, -0.5)] {
+            let ω = Bivector4::new(a, 0., 0., 0., 0., b);
+            let m = ω.exp().to_matrix();
+            let expected = (Rotor4::from_plane((0, 1), a) * Rotor4::from_plane((2, 3), b)).to_matrix();
+            assert_mat_eq(m, expected);
+        }
+
+        // Constant velocity: integrating in small steps gives the same rotation as
+        // one big one, regardless of the planes involved.
+        let ω = Bivector4::new(0.3, -0.2, 1., 0.5, 0., 0.7);
+        let mut rotor = Rotor4::identity();
+        for _ in 0..100 {
+            rotor = rotor.integrate(&ω, 0.02);
+        }
+        assert_mat_eq(rotor.to_matrix(), ω.scale(2.).exp().to_matrix());
+    }
+
+    #[test]
+    fn test_isoclinic() {
+        let rotor = Rotor4::from_angles(&array![0.3, -1.2, 2.5, 0.8, -0.4, 1.9]);
+        let (l, r) = rotor.isoclinic();
+        assert!((l.dot(&l) - 1.).abs() < 1e-5 && (r.dot(&r) - 1.).abs() < 1e-5);
+
+        let p = [0.5, -1., 2., 0.25];
+        let q = (l * Quaternion::from_array(p) * r).to_array();
+        let expected = rotor.rotate(p);
+        for k in 0..4 {
+            assert!((q[k] - expected[k]).abs() < 1e-5);
+        }
+        assert_mat_eq(Rotor4::from_isoclinic(&l, &r).to_matrix(), rotor.to_matrix());
+    }
+
+    #[test]
+    fn test_slerp() {
+        let a = Rotor4::from_angles(&array![0.3, -1.2, 2.5, 0.8, -0.4, 1.9]);
+        let b = Rotor4::from_angles(&array![-0.5, 0.2, 0.1, 2.8, 1., -0.6]);
+        assert_mat_eq(a.slerp(&b, 0.).to_matrix(), a.to_matrix());
+        assert_mat_eq(a.slerp(&b, 1.).to_matrix(), b.to_matrix());
+
+        // Halfway in one plane is half the angle, and the long way round isn't taken.
+        let turn = Rotor4::from_plane((1, 3), 1.2);
+        assert_mat_eq(Rotor4::identity().slerp(&turn, 0.5).to_matrix(),
+                      Rotor4::from_plane((1, 3), 0.6).to_matrix());
+        let negated = Rotor4 { s: -turn.s, xy: -turn.xy, xz: -turn.xz, xu: -turn.xu, yz: -turn.yz,
+            yu: -turn.yu, zu: -turn.zu, xyzu: -turn.xyzu };
+        assert_mat_eq(Rotor4::identity().slerp(&negated, 0.5).to_matrix(),
+                      Rotor4::from_plane((1, 3), 0.6).to_matrix());
+
+        // Constant speed: equal steps in t move a point equal distances.
+        let p = [1., 0.5, -0.3, 0.8];
+        let dist = |t0: f32, t1: f32| {
+            let (q0, q1) = (a.slerp(&b, t0).rotate(p), a.slerp(&b, t1).rotate(p));
+            (0..4).map(|k| (q0[k] - q1[k]).powi(2)).sum::<f32>().sqrt()
+        };
+        let first = dist(0., 0.25);
+        for &t in &[0.25, 0.5, 0.75] {
+            assert!((dist(t, t + 0.25) - first).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_compose() {
+        // Composing rotors matches multiplying their matrices.
+        let a = Rotor4::from_plane((0, 3), 0.6);
+        let b = Rotor4::from_plane((1, 2), -1.3);
+        assert_mat_eq((a * b).to_matrix(), transforms::dot_mm4(a.to_matrix(), b.to_matrix()));
+        assert_mat_eq((a * a.reverse()).to_matrix(), transforms::I4());
+        // A turn in xu, then one in yz, doesn't depend on the orientation angles' order.
+        let v = (b * a).rotate([1., 0., 0., 0.]);
+        assert!((v[3] - 0.6f32.sin()).abs() < 1e-6);
+    }
+}
@@ -7,7 +7,9 @@ use rand;
 //use simdnoise;
 use noise::{NoiseFn, Perlin};
 
+use rotor::Rotor4;
 use shape_maker;
+use transforms::Projection4;
 use types::{Camera, Lighting, Scene, Shape, CameraType};
 use util;
 
@@ -33,7 +35,8 @@ fn base_camera() -> Camera {
         aspect_4: 1.,
         near: 0.05,
         far: 600.,
-        fourd_proj_dist: 0.,
+        projection: Projection4::Orthographic,
+        rotor: None,
     }
 }
 
@@ -47,19 +50,29 @@ fn make_single_scene(aspect: f32, shape: Shape) -> Scene {
             θ: array![0., 0., τ / 2., 0., 0., 0.],
             fov: τ / 5.5,
             aspect,
-            fourd_proj_dist: 0.5,
+            projection: Projection4::Perspective { eye_dist: 2. },
             ..base_camera()
         },
         cam_type: CameraType::Single,
         color_max: 0.4,
         lighting: base_lighting,
         sensitivities: (0., 0.5, 0.2),
+        wireframe: false,
     }
 }
 
 pub fn hypercube_scene(aspect: f32) -> Scene {
-    make_single_scene(aspect, Shape::new(shape_maker::make_hypercube(1.), Array::zeros(4),
-        Array::zeros(6), Array::zeros(6), SHAPE_OP))
+    // Orient with a rotor, so turns in different planes combine the same way
+    // regardless of order.
+    let mut shape = Shape::new(shape_maker::make_hypercube(1.), Array::zeros(4),
+        Array::zeros(6), Array::zeros(6), SHAPE_OP);
+    shape.rotor = Some(Rotor4::identity());
+    make_single_scene(aspect, shape)
+}
+
+pub fn sixteencell_scene(aspect: f32) -> Scene {
+    make_single_scene(aspect, Shape::new(shape_maker::sixteencell(1.), Array::zeros(4),
+                                         Array::zeros(6), Array::zeros(6), SHAPE_OP))
 }
 
 pub fn fivecell_scene(aspect: f32) -> Scene {
@@ -74,12 +87,60 @@ pub fn twentyfourcell_scene(aspect: f32) -> Scene {
     scene
 }
 
+pub fn sixhundredcell_scene(aspect: f32) -> Scene {
+    let mut scene = make_single_scene(aspect, Shape::new(shape_maker::sixhundredcell(1.5), Array::zeros(4),
+                                         Array::zeros(6), Array::zeros(6), SHAPE_OP));
+    scene.cam.position[2] = -3.0;
+    scene
+}
+
+pub fn onehundredtwentycell_scene(aspect: f32) -> Scene {
+    let mut scene = make_single_scene(aspect, Shape::new(shape_maker::onehundredtwentycell(1.5), Array::zeros(4),
+                                         Array::zeros(6), Array::zeros(6), SHAPE_OP));
+    scene.cam.position[2] = -3.0;
+    scene
+}
+
 pub fn spherinder_scene(aspect: f32) -> Scene {
     make_single_scene(aspect, Shape::new(shape_maker::spherinder((3., 0.5), 64),
                                          Array::zeros(4),
                                          Array::zeros(6), Array::zeros(6), SHAPE_OP))
 }
 
+pub fn spherecone_scene(aspect: f32) -> Scene {
+    make_single_scene(aspect, Shape::new(shape_maker::pyramid_4d(shape_maker::sphere(0.5, 32), 1.),
+                                         Array::zeros(4),
+                                         Array::zeros(6), Array::zeros(6), SHAPE_OP))
+}
+
+pub fn cubecone_scene(aspect: f32) -> Scene {
+    make_single_scene(aspect, Shape::new(shape_maker::pyramid_4d(shape_maker::cube(1.), 1.),
+                                         Array::zeros(4),
+                                         Array::zeros(6), Array::zeros(6), SHAPE_OP))
+}
+
+pub fn duoprism_scene(aspect: f32) -> Scene {
+    make_single_scene(aspect, Shape::new(shape_maker::duoprism(3, 5, 0.7), Array::zeros(4),
+                                         Array::zeros(6), Array::zeros(6), SHAPE_OP))
+}
+
+pub fn duocylinder_scene(aspect: f32) -> Scene {
+    make_single_scene(aspect, Shape::new(shape_maker::duocylinder((0.7, 0.7), 32), Array::zeros(4),
+                                         Array::zeros(6), Array::zeros(6), SHAPE_OP))
+}
+
+pub fn glome_scene(aspect: f32) -> Scene {
+    make_single_scene(aspect, Shape::new(shape_maker::glome(1., 24), Array::zeros(4),
+                                         Array::zeros(6), Array::zeros(6), SHAPE_OP))
+}
+
+pub fn clifford_torus_scene(aspect: f32) -> Scene {
+    // Rotating in the xy and zu planes at once is a double rotation: the torus
+    // slides along itself.
+    make_single_scene(aspect, Shape::new(shape_maker::clifford_torus(1., 48), Array::zeros(4),
+                                         Array::zeros(6), array![0.5, 0., 0., 0., 0., 0.5], SHAPE_OP))
+}
+
 pub fn origin_scene(aspect: f32) -> Scene {
     make_single_scene(aspect, Shape::new(shape_maker::origin((1., 0.1), 32),
                                          Array::zeros(4),
@@ -158,9 +219,12 @@ pub fn world_scene(aspect: f32) -> Scene {
     let spiss_map_2d = Array::zeros((terrain_res, terrain_res));
 
     let mut shape_list = Vec::new();
-    shape_list.push(Shape::new(shape_maker::terrain((terrain_size, terrain_size), terrain_res as u32,
-                                                    height_map_2d, spiss_map_2d),
-                    array![0., -1., 0., 0.], Array::zeros(6), Array::zeros(6), 1.));
+    let mut ground = Shape::new(shape_maker::terrain((terrain_size, terrain_size), terrain_res as u32,
+                                                     height_map_2d, spiss_map_2d),
+                                array![0., -1., 0., 0.], Array::zeros(6), Array::zeros(6), 1.);
+    // Smooth normals let the terrain's faces share vertices in the buffers.
+    ground.smooth_normals = true;
+    shape_list.push(ground);
 
     for i in 0..n_shapes {
 //        let shape_type = rand::random::<f32>();
@@ -239,6 +303,7 @@ pub fn world_scene(aspect: f32) -> Scene {
         color_max: 10.,
         lighting: base_lighting,
         sensitivities: (5., 0.2, 0.2),
+        wireframe: false,
     }
 }
 
@@ -267,7 +332,7 @@ pub fn grid_scene(aspect: f32) -> Scene {
     let grid_size: usize = 12;
     let grid = Array3::zeros((grid_size, grid_size, grid_size));
     let shapes = shape_maker::hypergrid((200., 200., 200.), grid_size as u32,
-                                        grid);
+                                            grid);
 
     Scene {
         shapes,
@@ -280,6 +345,7 @@ pub fn grid_scene(aspect: f32) -> Scene {
         color_max: 100.,
         lighting: base_lighting,
         sensitivities: (5., 0.5, 0.2),
+        wireframe: false,
     }
 }
 
@@ -289,15 +355,19 @@ pub fn grid_scene_4d(aspect: f32) -> Scene {
 
     Scene {
         shapes,
+        // Looking around in 4d mixes all six planes, so keep the camera's
+        // orientation as a rotor.
         cam: Camera {
             position: array![0., 0., -1., 0.],
             aspect,
+            rotor: Some(Rotor4::identity()),
             ..base_camera()
         },
         cam_type: CameraType::Free,
         color_max: 150.,
         lighting: base_lighting,
         sensitivities: (5., 0.5, 0.2),
+        wireframe: false,
     }
 }
 
@@ -351,14 +421,17 @@ pub fn plot_scene(aspect: f32) -> Scene {
         }
     }
 
-    let mut plot = shape_maker::terrain((10., 10.), res as u32,
+    let plot = shape_maker::terrain((10., 10.), res as u32,
                                                       height_grid, spiss_grid);
-
     let origin = shape_maker::origin((4., 0.1), 10);
-    plot = shape_maker::combine_meshes(plot, vec![(origin, [0., 0., 0., 0.])]);
 
+    // The plot is a smooth surface, but the origin's arrows have sharp edges, so
+    // they're separate shapes.
     let mut shapes = HashMap::new();
-        shapes.insert(0, Shape::new(plot, Array::zeros(4), Array::zeros(6), Array::zeros(6), 1.));
+    let mut plot_shape = Shape::new(plot, Array::zeros(4), Array::zeros(6), Array::zeros(6), 1.);
+    plot_shape.smooth_normals = true;
+    shapes.insert(0, plot_shape);
+    shapes.insert(1, Shape::new(origin, Array::zeros(4), Array::zeros(6), Array::zeros(6), 1.));
 
 
     Scene {
@@ -373,5 +446,6 @@ pub fn plot_scene(aspect: f32) -> Scene {
         color_max: 10.,
         lighting: base_lighting,
         sensitivities: (5., 0.5, 0.2),
+        wireframe: false,
     }
 }
\ No newline at end of file
@@ -3,8 +3,9 @@ use std::f32::consts::PI;
 
 use ndarray::prelude::*;
 
+use hull::{self, HullError};
 use transforms;
-use types::{Vertex, Mesh, Normal, Shape};
+use types::{CoxeterGroup, FaceGroup, Vertex, Mesh, MeshTransform, Normal, Pt2D, Shape};
 use util;
 
 const τ: f32 = 2. * PI;
@@ -15,35 +16,95 @@ const τ: f32 = 2. * PI;
 // Nodes are set up here so that 0 is at their center; this is used for scaling,
 // rotation, and positioning in the world.
 
-pub fn combine_meshes(mut base: Mesh, meshes: Vec<(Mesh, [f32; 4])>) -> Mesh{
-    // The array in the meshes tuple is position offset for that shape.
-    let mut id_addition = base.vertices.len() as u32;
-    for (mesh, offset) in &meshes {
-        for (id, vertex) in &mesh.vertices {
-            // For the roof, modify the ids to be unique.
-            base.vertices.insert(
-                id + id_addition,
-                Vertex::new(vertex.position.0 + offset[0], vertex.position.1 + offset[1],
-                            vertex.position.2 + offset[2], vertex.position.3 + offset[3]
-                )
+pub fn combine_meshes(mut base: Mesh, meshes: Vec<(Mesh, MeshTransform)>) -> Mesh {
+    // Add meshes to base, each placed with its transform. The added meshes'
+    // vertices go after base's, so their indices are shifted.
+    for (mesh, transform) in &meshes {
+        let id_addition = base.vertices.len() as u32;
+        let model = transforms::make_model_mat4(&transform.orientation, transform.scale);
+        let rotation = transforms::make_rotator4(&transform.orientation);
+        let offset = transform.offset;
+
+        for vertex in &mesh.vertices {
+            let p = vertex.position;
+            let p = transforms::dot_mv4(model, [p.0, p.1, p.2, p.3]);
+            base.vertices.push(
+                Vertex::new(p[0] + offset[0], p[1] + offset[1], p[2] + offset[2], p[3] + offset[3])
             );
         }
 
+        let face_addition = base.faces_vert.len() as u32;
+        for cell in &mesh.cells {
+            base.cells.push(cell + face_addition);
+        }
+
         for face in &mesh.faces_vert {
             base.faces_vert.push(face + id_addition);
         }
 
-        for normal in &mesh.normals {  // todo rotate normals!
-            base.normals.push(normal.clone());
+        // Normals only rotate. In 4d, a negative scale is -I, which has determinant +1:
+        // it's a rotation (by a half turn in two planes), not a reflection, so it
+        // doesn't turn the mesh inside out. Normals just point the opposite way, along
+        // with everything else.
+        let sign = transform.scale.signum();
+        for normal in &mesh.normals {
+            let n = normal.normal;
+            let n = transforms::dot_mv4(rotation, [n.0, n.1, n.2, n.3]);
+            base.normals.push(Normal::new(sign * n[0], sign * n[1], sign * n[2], sign * n[3]));
         }
-
-        id_addition += mesh.vertices.len() as u32;
     }
 
     base.make_tris();
     base
 }
 
+pub fn convex_hull(points: &[Vertex]) -> Mesh {
+    // Build a mesh from the boundary of the convex hull of a 4d point set. Points
+    // that aren't corners of the hull are dropped, and the remaining vertices are
+    // renumbered in the order they appear in `points`. Panics unless the points
+    // span all four dimensions; see try_convex_hull.
+    match try_convex_hull(points) {
+        Ok(mesh) => mesh,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+pub fn try_convex_hull(points: &[Vertex]) -> Result<Mesh, HullError> {
+    // As convex_hull, but returns an error for empty, non-finite or flat point sets.
+    let coords: Vec<[f64; 4]> = points.iter()
+        .map(|p| [p.position.0 as f64, p.position.1 as f64, p.position.2 as f64, p.position.3 as f64])
+        .collect();
+
+    let hull = hull::convex_hull(&coords)?;
+
+    let mut vertices = Vec::new();
+    let mut new_ids = HashMap::new();
+    for (id, i) in hull.verts.iter().enumerate() {
+        vertices.push(points[*i]);
+        new_ids.insert(*i, id as u32);
+    }
+
+    let mut faces_vert = Vec::new();
+    let mut normals = Vec::new();
+    let mut cells = vec![Vec::new(); hull.cells.len()];
+    for face in &hull.faces {
+        cells[face.cells.0].push(faces_vert.len() as u32);
+        cells[face.cells.1].push(faces_vert.len() as u32);
+        faces_vert.push(face.verts.iter().map(|i| new_ids[i]).collect());
+
+        // The face's normal bisects the outward normals of the two cells meeting there.
+        let (n0, n1) = (hull.cells[face.cells.0].normal, hull.cells[face.cells.1].normal);
+        let sum = [n0[0] + n1[0], n0[1] + n1[1], n0[2] + n1[2], n0[3] + n1[3]];
+        let len = sum.iter().fold(0., |acc, c| acc + c * c).sqrt();
+        normals.push(Normal::new((sum[0] / len) as f32, (sum[1] / len) as f32,
+                                 (sum[2] / len) as f32, (sum[3] / len) as f32));
+    }
+
+    let mut result = Mesh::new(vertices, faces_vert, normals);
+    result.cells = cells.into_iter().map(Array::from_vec).collect();
+    Ok(result)
+}
+
 pub fn box_(lens: (f32, f32, f32)) -> Mesh {
     // Make a rectangular prism.  Use negative lengths to draw in the opposite
     // direction.
@@ -143,7 +204,7 @@ pub fn house(lens: (f32, f32, f32)) -> Mesh {
         (lens.0 * 1.2, lens.1 / 3., lens.2 * 1.2),
     );
 
-    combine_meshes(base, vec![(roof, [0., lens.1 / 2., 0., 0.])])
+    combine_meshes(base, vec![(roof, MeshTransform::offset([0., lens.1 / 2., 0., 0.]))])
 }
 
 pub fn cube(side_len: f32) -> Mesh {
@@ -152,19 +213,65 @@ pub fn cube(side_len: f32) -> Mesh {
     box_((side_len, side_len, side_len))
 }
 
+pub fn sphere(radius: f32, res: u32) -> Mesh {
+    // A 'UV' sphere, with res vertices around each line of latitude, and res / 2
+    // bands of latitude. Bands next to the poles are triangles; the rest are squares.
+    assert_eq!(res % 2, 0);
+    assert!(res >= 4);
+
+    let rings = res / 2 - 1;  // Lines of latitude, not including the poles.
+    let top = rings * res;
+    let bottom = top + 1;
+
+    let mut vertices = HashMap::new();
+    for j in 0..rings {
+        let θ = (j + 1) as f32 * τ / res as f32;  // Angle from the top pole.
+        for i in 0..res {
+            let φ = i as f32 * τ / res as f32;
+            vertices.insert(j * res + i, Vertex::new(
+                radius * θ.sin() * φ.cos(), radius * θ.cos(), radius * θ.sin() * φ.sin(), 0.
+            ));
+        }
+    }
+    vertices.insert(top, Vertex::new(0., radius, 0., 0.));
+    vertices.insert(bottom, Vertex::new(0., -radius, 0., 0.));
+
+    let mut faces_vert = Vec::new();
+    for i in 0..res {
+        let next = (i + 1) % res;
+        faces_vert.push(array![top, next, i]);
+        for j in 0..rings - 1 {
+            faces_vert.push(array![j * res + i, j * res + next, (j + 1) * res + next, (j + 1) * res + i]);
+        }
+        faces_vert.push(array![(rings - 1) * res + i, (rings - 1) * res + next, bottom]);
+    }
+
+    // The sphere's centered on the origin, so each face's normal points towards
+    // its centroid.
+    let normals = faces_vert.iter().map(|face| {
+        let centroid = face.iter().fold((0., 0., 0.), |acc, id| {
+            let p = vertices[id].position;
+            (acc.0 + p.0, acc.1 + p.1, acc.2 + p.2)
+        });
+        unit_normal(centroid.0, centroid.1, centroid.2, 0.)
+    }).collect();
+
+    Mesh::new(vertices, faces_vert, normals)
+}
+
 pub fn fivecell(radius: f32) -> Mesh {
+    // A pyramid over a tetrahedron.
     let coords = [
-        [-(2./3. as f32).sqrt(), -1./3., -(2./9. as f32).sqrt(), 0.],  // left base
-        [(2./3. as f32).sqrt(), -1./3., -(2./9. as f32).sqrt(), 0.],  // right base
-        [0., -1./3., (8./9. as f32).sqrt(), 0.],  // Back base
-        [0., 1., 0., 0.],  // Top
-        [0., 0., 0., 1.],  // middle
+        [-(2./3. as f32).sqrt(), -1./3., -(2./9. as f32).sqrt()],  // left base
+        [(2./3. as f32).sqrt(), -1./3., -(2./9. as f32).sqrt()],  // right base
+        [0., -1./3., (8./9. as f32).sqrt()],  // Back base
+        [0., 1., 0.],  // Top
     ];
 
     let mut vertices = HashMap::new();
     for (id, coord) in coords.iter().enumerate() {
         vertices.insert(id as u32, Vertex::new(
-            coord[0] * radius/2., coord[1] * radius/2., coord[2] * radius/2., coord[3] * radius/2.
+            coord[0] * radius/2., coord[1] * radius/2., coord[2] * radius/2., 0.
         ));
     }
 
@@ -173,141 +280,134 @@ pub fn fivecell(radius: f32) -> Mesh {
         array![0, 1, 3],  // Front
         array![1, 2, 3],  // Right
         array![2, 0, 3],  // Left
-
-        array![4, 0, 1],  // Center front
-        array![4, 1, 2],  // Center right
-        array![4, 2, 0],  // Center left
-
-        array![4, 0, 3],  // Center left top
-        array![4, 1, 3],  // Center right top
-        array![4, 2, 3],  // Center back top
     ];
 
-    let normals = vec![  // todo fix this.
-        Normal::new(0., 0., 1., 0.),
-        Normal::new(0., 0., -1., 0.),
-        Normal::new(0., 1., 0., 0.),
-        Normal::new(0., -1., 0., 0.),
-
-        Normal::new(-1., 0., 0., 0.),
-        Normal::new(1., 0., 0., 0.),
-        Normal::new(0., 0., 0., 1.),
-
-        Normal::new(0., 0., 0., -1.),
-        Normal::new(0., 0., 0., -1.),
-        Normal::new(0., 0., 0., -1.),
+    // The tetrahedron's centered on the origin, so each face's normal points
+    // away from the vertex it doesn't include.
+    let normals = vec![
+        Normal::new(-coords[3][0], -coords[3][1], -coords[3][2], 0.),
+        Normal::new(-coords[2][0], -coords[2][1], -coords[2][2], 0.),
+        Normal::new(-coords[0][0], -coords[0][1], -coords[0][2], 0.),
+        Normal::new(-coords[1][0], -coords[1][1], -coords[1][2], 0.),
     ];
 
-    Mesh::new(vertices, faces_vert, normals)
+    pyramid_4d(Mesh::new(vertices, faces_vert, normals), radius/2.)
 }
 
 pub fn hyperrect(lens: (f32, f32, f32, f32)) -> Mesh {
     // Make a 4d hypercube.
+    let mut result = prism_4d(box_((lens.0, lens.1, lens.2)), lens.3);
 
-    let coords = [
-        // Front inner
-        [-1., -1., -1., -1.],
-        [1., -1., -1., -1.],
-        [1., 1., -1., -1.],
-        [-1., 1., -1., -1.],
-
-        // Back inner
-        [-1., -1., 1., -1.],
-        [1., -1., 1., -1.],
-        [1., 1., 1., -1.],
-        [-1., 1., 1., -1.],
-
-        // Front outer
-        [-1., -1., -1., 1.],
-        [1., -1., -1., 1.],
-        [1., 1., -1., 1.],
-        [-1., 1., -1., 1.],
-
-        // Back outer
-        [-1., -1., 1., 1.],
-        [1., -1., 1., 1.],
-        [1., 1., 1., 1.],
-        [-1., 1., 1., 1.],
-    ];
+    // Center it on the origin in w, as well as x, y and z.
+    for vertex in &mut result.vertices {
+        vertex.position.3 -= lens.3 / 2.;
+    }
+    result
+}
 
-    let mut vertices = HashMap::new();
-    for (id, coord) in coords.iter().enumerate() {
-        vertices.insert(id as u32, Vertex::new(
-            coord[0] * lens.0 / 2., coord[1] * lens.1 / 2.,
-            coord[2] * lens.2 / 2., coord[3] * lens.3 / 2.
-        ));
+pub fn make_hypercube(side_len: f32) -> Mesh {
+    // Convenience function.
+    hyperrect((side_len, side_len, side_len, side_len))
+}
+
+pub fn sixteencell(radius: f32) -> Mesh {
+    // The 16-cell, or hyperoctahedron; the dual of the tesseract. Its 8 vertices
+    // are the permutations of (±1, 0, 0, 0), scaled by radius. It has 24 edges,
+    // 32 triangular faces and 16 tetrahedral cells.
+    let mut points = Vec::new();
+    for i in 0..4 {
+        for sign in &[-1., 1.] {
+            let mut coord = [0.; 4];
+            coord[i] = sign * radius;
+            points.push(Vertex::new(coord[0], coord[1], coord[2], coord[3]));
+        }
     }
 
-    let faces_vert = vec![  // Vertex indices for each face.
-                            array![0, 1, 2, 3],  // Front inner
-                            array![4, 5, 6, 7],  // Back inner
-                            array![3, 2, 6, 7],  // Top inner
-                            array![0, 1, 5, 4],  // Bottom inner
-                            array![0, 4, 7, 3],  // Left inner
-                            array![1, 5, 6, 2],  // Right inner
-
-                            array![8, 9, 10, 11],  // Front outer
-                            array![12, 13, 14, 15],  // Back outer
-                            array![11, 10, 14, 15],  // Top outer
-                            array![8, 9, 13, 12],  // Bottom outer
-                            array![8, 12, 15, 11],  // Left outer
-                            array![9, 13, 14, 10],  // Right outer
-
-                            array![8, 9, 1, 0],  // Front bottom
-                            array![12, 13, 5, 4],  // Back bottom
-                            array![12, 8, 0, 4],  // Left bottom
-                            array![9, 13, 5, 1],  // Right bottom
-
-                            array![11, 10, 2, 3],  // Front top
-                            array![15, 14, 6, 7],  // Back top
-                            array![15, 11, 3, 7],  // Left top
-                            array![14, 10, 2, 6],  // Right top
-
-                            array![11, 8, 0, 3],  // Left forward
-                            array![15, 12, 4, 7],  // Left back
-                            array![10, 9, 1, 2],  // Right forward
-                            array![14, 13, 5, 6],  // Right back
-    ];
+    convex_hull(&points)
+}
 
-    // todo QC this; it's a guess.  Attempting to ignore w for this.
-    let normals = vec![
-        Normal::new(0., 0., -1., 0.),
-        Normal::new(0., 0., 1., 0.),
-        Normal::new(0., 1., 0., 0.),
-        Normal::new(0., -1., 0., 0.),
+pub fn polygon(n: u32, radius: f32) -> Vec<Pt2D> {
+    // The vertices of a regular polygon, counter-clockwise, starting on the x axis.
+    (0..n).map(|i| {
+        let θ = i as f32 * τ / n as f32;
+        Pt2D { x: radius * θ.cos(), y: radius * θ.sin() }
+    }).collect()
+}
 
-        Normal::new(-1., 0., 0., 0.),
-        Normal::new(1., 0., 0., 0.),
-        Normal::new(0., 0., -1., 0.),
-        Normal::new(0., 0., 1., 0.),
+fn edge_normals(poly: &[Pt2D]) -> Vec<(f32, f32)> {
+    // Outward unit normals of a convex polygon's edges; edge i runs from vertex i
+    // to vertex i + 1.
+    let n = poly.len();
+    let area = (0..n).fold(0., |acc, i| {
+        let (a, b) = (&poly[i], &poly[(i + 1) % n]);
+        acc + a.x * b.y - b.x * a.y
+    });
+    let sign = if area > 0. { 1. } else { -1. };  // Counter-clockwise has positive area.
+
+    (0..n).map(|i| {
+        let (a, b) = (&poly[i], &poly[(i + 1) % n]);
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let len = (dx * dx + dy * dy).sqrt();
+        (sign * dy / len, -sign * dx / len)
+    }).collect()
+}
 
-        Normal::new(0., 1., 0., 0.),
-        Normal::new(0., -1., 0., 0.),
-        Normal::new(-1., 0., 0., 0.),
-        Normal::new(1., 0., 0., 0.),
+pub fn polygon_product(p: &[Pt2D], q: &[Pt2D]) -> Mesh {
+    // The Cartesian product of two convex polygons, with p in the x-y plane and q
+    // in the z-w plane. Its cells are prisms: each edge of p times q, and p times
+    // each edge of q. Each edge of one polygon times an edge of the other makes a
+    // square face, and each vertex of one times the other polygon is a face.
+    // (The product of a polygon and a 3d mesh would be 5d; the 4d product of a 3d
+    // mesh is with a line segment: see prism_4d.)
+    let (np, nq) = (p.len() as u32, q.len() as u32);
+    let id = |i: u32, j: u32| (i % np) * nq + j % nq;
 
-        Normal::new(0., 0., -1., 0.),
-        Normal::new(0., 0., 1., 0.),
-        Normal::new(0., 1., 0., 0.),
-        Normal::new(0., -1., 0., 0.),
+    let mut vertices = HashMap::new();
+    for (i, a) in p.iter().enumerate() {
+        for (j, b) in q.iter().enumerate() {
+            vertices.insert(id(i as u32, j as u32), Vertex::new(a.x, a.y, b.x, b.y));
+        }
+    }
 
-        Normal::new(-1., 0., 0., 0.),
-        Normal::new(1., 0., 0., 0.),
-        Normal::new(0., 0., -1., 0.),
-        Normal::new(0., 0., 1., 0.),
+    let (p_normals, q_normals) = (edge_normals(p), edge_normals(q));
 
-        Normal::new(0., 1., 0., 0.),
-        Normal::new(0., -1., 0., 0.),
-        Normal::new(-1., 0., 0., 0.),
-        Normal::new(1., 0., 0., 0.),
-    ];
+    let mut faces_vert = Vec::new();
+    let mut normals = Vec::new();
+
+    // Squares lie on the cells of both their edges.
+    for i in 0..np {
+        for j in 0..nq {
+            faces_vert.push(array![id(i, j), id(i + 1, j), id(i + 1, j + 1), id(i, j + 1)]);
+            let (m, n) = (p_normals[i as usize], q_normals[j as usize]);
+            normals.push(unit_normal(m.0, m.1, n.0, n.1));
+        }
+    }
+
+    // Copies of q lie on the cells of the two edges of p next to their vertex.
+    for i in 0..np {
+        faces_vert.push((0..nq).map(|j| id(i, j)).collect());
+        let (m0, m1) = (p_normals[((i + np - 1) % np) as usize], p_normals[i as usize]);
+        normals.push(unit_normal(m0.0 + m1.0, m0.1 + m1.1, 0., 0.));
+    }
+
+    for j in 0..nq {
+        faces_vert.push((0..np).map(|i| id(i, j)).collect());
+        let (n0, n1) = (q_normals[((j + nq - 1) % nq) as usize], q_normals[j as usize]);
+        normals.push(unit_normal(0., 0., n0.0 + n1.0, n0.1 + n1.1));
+    }
 
     Mesh::new(vertices, faces_vert, normals)
 }
 
-pub fn make_hypercube(side_len: f32) -> Mesh {
-    // Convenience function.
-    hyperrect((side_len, side_len, side_len, side_len))
+pub fn duoprism(p: u32, q: u32, radius: f32) -> Mesh {
+    // The p,q-duoprism: The product of regular p- and q-gons. The 4,4-duoprism is
+    // a tesseract.
+    polygon_product(&polygon(p, radius), &polygon(q, radius))
+}
+
+pub fn duocylinder(radii: (f32, f32), res: u32) -> Mesh {
+    // The product of two discs, approximated as the product of two res-gons.
+    polygon_product(&polygon(res, radii.0), &polygon(res, radii.1))
 }
 
 fn avg_normals(normals: Vec<Normal>) -> Normal {
@@ -320,6 +420,155 @@ fn avg_normals(normals: Vec<Normal>) -> Normal {
     Normal::new(x/len , y/len, z/len, w/len)
 }
 
+fn unit_normal(x: f32, y: f32, z: f32, w: f32) -> Normal {
+    let len = (x * x + y * y + z * z + w * w).sqrt();
+    if len == 0. {
+        return Normal::new(0., 0., 0., 0.)
+    }
+    Normal::new(x / len, y / len, z / len, w / len)
+}
+
+fn edge_faces(mesh: &Mesh) -> Vec<((u32, u32), Vec<usize>)> {
+    // Find each edge of a mesh, with the faces it borders. Edges are sorted,
+    // with the lower vertex id first.
+    let mut edge_faces = HashMap::new();
+    for (face_id, face) in mesh.faces_vert.iter().enumerate() {
+        for i in 0..face.len() {
+            let (a, b) = (face[i], face[(i + 1) % face.len()]);
+            let edge = if a < b { (a, b) } else { (b, a) };
+            edge_faces.entry(edge).or_insert_with(Vec::new).push(face_id);
+        }
+    }
+    let mut result: Vec<((u32, u32), Vec<usize>)> = edge_faces.into_iter().collect();
+    result.sort();
+    result
+}
+
+pub fn prism_4d(mesh: Mesh, length: f32) -> Mesh {
+    // Extrude a 3d mesh (with w = 0) along w, to make a 4d prism: eg a cube becomes
+    // a tesseract, and a sphere a spherinder. The original faces are copied to
+    // w = length, and each edge of the mesh sweeps out a square joining the copies.
+    // Each face's normal bisects the normals of the two cells that meet there:
+    // The caps point along ∓w, and each of the original faces sweeps out a cell
+    // pointing along that face's normal.
+    let id_addition = mesh.vertices.len() as u32;
+
+    let mut vertices: Vec<Vertex> = mesh.vertices.iter()
+        .map(|v| Vertex::new(v.position.0, v.position.1, v.position.2, 0.))
+        .collect();
+    vertices.extend(mesh.vertices.iter()
+        .map(|v| Vertex::new(v.position.0, v.position.1, v.position.2, length)));
+
+    // Each face of the mesh sweeps out a cell. Its normal is perpendicular to the
+    // face and to w: sum the generalized cross products of consecutive corners, so
+    // it works for concave faces too. Keep it on the same side as the mesh's normal.
+    let w = Vertex::new(0., 0., 0., 1.);
+    let face_normals: Vec<Normal> = mesh.faces_vert.iter().zip(&mesh.normals).map(|(face, given)| {
+        let corners: Vec<Vertex> = face.iter().map(|id| mesh.vertices[*id as usize]).collect();
+        let mut n = (0., 0., 0.);
+        for i in 0..corners.len() {
+            let a = corners[i].subtract(&corners[0]);
+            let b = corners[(i + 1) % corners.len()].subtract(&corners[0]);
+            let cross = a.cross4(&b, &w).normal;
+            n = (n.0 + cross.0, n.1 + cross.1, n.2 + cross.2);
+        }
+
+        let g = given.normal;
+        let sign = if n.0 * g.0 + n.1 * g.1 + n.2 * g.2 < 0. { -1. } else { 1. };
+        unit_normal(sign * n.0, sign * n.1, sign * n.2, 0.)
+    }).collect();
+
+    let mut faces_vert = Vec::new();
+    let mut normals = Vec::new();
+
+    for (face, n) in mesh.faces_vert.iter().zip(&face_normals) {
+        faces_vert.push(face.clone());
+        normals.push(unit_normal(n.normal.0, n.normal.1, n.normal.2, -1.));
+
+        faces_vert.push(face + id_addition);
+        normals.push(unit_normal(n.normal.0, n.normal.1, n.normal.2, 1.));
+    }
+
+    // The caps are cells, and so is each face swept along w: Its two copies, and
+    // the squares swept out by its edges.
+    let num_faces = mesh.faces_vert.len() as u32;
+    let mut cells = vec![
+        (0..num_faces).map(|f| 2 * f).collect(),
+        (0..num_faces).map(|f| 2 * f + 1).collect(),
+    ];
+    let mut side_cells: Vec<Vec<u32>> = (0..num_faces).map(|f| vec![2 * f, 2 * f + 1]).collect();
+
+    for ((a, b), edge_faces) in edge_faces(&mesh) {
+        for f in &edge_faces {
+            side_cells[*f].push(faces_vert.len() as u32);
+        }
+        faces_vert.push(array![a, b, b + id_addition, a + id_addition]);
+        let n = avg_normals(edge_faces.iter().map(|f| face_normals[*f]).collect());
+        normals.push(unit_normal(n.normal.0, n.normal.1, n.normal.2, 0.));
+    }
+    cells.extend(side_cells.into_iter().map(Array::from_vec));
+
+    let mut result = Mesh::new(vertices, faces_vert, normals);
+    result.cells = cells;
+    result
+}
+
+pub fn pyramid_4d(base: Mesh, apex_w: f32) -> Mesh {
+    // Make a 4d pyramid (or cone) from a 3d mesh (with w = 0) and an apex at
+    // (0, 0, 0, apex_w). Its cells are the base, plus a cone joining each face of
+    // the base to the apex. The base should be convex, and contain the origin in
+    // x, y and z. As with prism_4d, face normals bisect the normals of the two
+    // cells that meet at that face. apex_w can't be 0, which would flatten the
+    // pyramid into its base.
+    assert!(apex_w != 0. && apex_w.is_finite(), "The pyramid's apex must be off the w = 0 hyperplane.");
+    let apex = base.vertices.len() as u32;
+
+    let mut vertices: Vec<Vertex> = base.vertices.iter()
+        .map(|v| Vertex::new(v.position.0, v.position.1, v.position.2, 0.))
+        .collect();
+    vertices.push(Vertex::new(0., 0., 0., apex_w));
+
+    // The cone over a face with 3d normal n lies on the hyperplane through the face
+    // and the apex. Its normal is (n, d / apex_w), where d is n · p for any point p
+    // of the face.
+    let cone_normals: Vec<Normal> = base.faces_vert.iter().zip(&base.normals).map(|(face, n)| {
+        let n = unit_normal(n.normal.0, n.normal.1, n.normal.2, 0.).normal;
+        let p = vertices[face[0] as usize].position;
+        let d = n.0 * p.0 + n.1 * p.1 + n.2 * p.2;
+        unit_normal(n.0, n.1, n.2, d / apex_w)
+    }).collect();
+
+    // The base's cell faces away from the apex.
+    let base_normal = -apex_w.signum();
+
+    let mut faces_vert = Vec::new();
+    let mut normals = Vec::new();
+
+    for (face, n) in base.faces_vert.iter().zip(&cone_normals) {
+        faces_vert.push(face.clone());
+        normals.push(unit_normal(n.normal.0, n.normal.1, n.normal.2, n.normal.3 + base_normal));
+    }
+
+    // Each edge of the base joins the apex in a triangle. A cone's cell is its
+    // face of the base, and the triangles on that face's edges.
+    let num_faces = base.faces_vert.len() as u32;
+    let mut cone_cells: Vec<Vec<u32>> = (0..num_faces).map(|f| vec![f]).collect();
+
+    for ((a, b), edge_faces) in edge_faces(&base) {
+        for f in &edge_faces {
+            cone_cells[*f].push(faces_vert.len() as u32);
+        }
+        faces_vert.push(array![a, b, apex]);
+        let n = avg_normals(edge_faces.iter().map(|f| cone_normals[*f]).collect());
+        normals.push(unit_normal(n.normal.0, n.normal.1, n.normal.2, n.normal.3));
+    }
+
+    let mut result = Mesh::new(vertices, faces_vert, normals);
+    result.cells = vec![(0..num_faces).collect()];
+    result.cells.extend(cone_cells.into_iter().map(Array::from_vec));
+    result
+}
+
 pub fn terrain(dims: (f32, f32), res: u32,
                height_map: Array2<f32>, spissitude_map: Array2<f32>,
                ) -> Mesh {
@@ -333,11 +582,9 @@ pub fn terrain(dims: (f32, f32), res: u32,
     // todo include some of your code streamlining from make_spherinder;
     // todo better yet: Combine these two with a helper func.
 
-    let mut vertices = HashMap::new();
+    let mut vertices = Vec::new();
     let mut normals = Vec::new();
 
-    let mut id = 0;
-
     let mut active_ind = 0;
     // Faces for this terrain are triangles. Don't try to make square faces;
     // they'd really have creases down a diagonal.
@@ -351,13 +598,12 @@ pub fn terrain(dims: (f32, f32), res: u32,
             let spissitude = spissitude_map[[i as usize, j as usize]];
             // You could change which planes this is over by rearranging
             // these node points.
-            vertices.insert(id, Vertex::new(
+            vertices.push(Vertex::new(
                 x,
                 height,
                 z,
                 spissitude,
             ));
-            id += 1;
         }
     }
 
@@ -376,7 +622,7 @@ pub fn terrain(dims: (f32, f32), res: u32,
             );
 
             let current_ind = active_ind + j;
-            let current_vert = &vertices[&(current_ind)];
+            let current_vert = &vertices[(current_ind) as usize];
 
             // Compute normal as the avg of the norm of all 4 neighboring faces.
             // We are ignoring w, for now.
@@ -385,49 +631,56 @@ pub fn terrain(dims: (f32, f32), res: u32,
             // Start at North; go around clockwise.
             if i != res - 2 && j != res - 2 {  // not at ne corner
                 edge_pairs.push((
-                    vertices[&(current_ind + 1)].subtract(current_vert),  // n
-                    vertices[&(current_ind + res + 1)].subtract(current_vert)  // ne
+                    vertices[(current_ind + 1) as usize].subtract(current_vert),  // n
+                    vertices[(current_ind + res + 1) as usize].subtract(current_vert)  // ne
                 ));
                 edge_pairs.push((
-                    vertices[&(current_ind + res + 1)].subtract(current_vert),  // ne
-                    vertices[&(current_ind + res)].subtract(current_vert)  // e
+                    vertices[(current_ind + res + 1) as usize].subtract(current_vert),  // ne
+                    vertices[(current_ind + res) as usize].subtract(current_vert)  // e
                 ));
             }
             if i != res - 2 && j != 0 {  // not at se corner
                 edge_pairs.push((
-                    vertices[&(current_ind + res)].subtract(current_vert),  // e
-                    vertices[&(current_ind + res - 1)].subtract(current_vert)  // se
+                    vertices[(current_ind + res) as usize].subtract(current_vert),  // e
+                    vertices[(current_ind + res - 1) as usize].subtract(current_vert)  // se
                 ));
                 edge_pairs.push((
-                    vertices[&(current_ind + res - 1)].subtract(current_vert),  // se
-                    vertices[&(current_ind - 1)].subtract(current_vert)  // s
+                    vertices[(current_ind + res - 1) as usize].subtract(current_vert),  // se
+                    vertices[(current_ind - 1) as usize].subtract(current_vert)  // s
                 ));
             }
             if i != 0 && j != 0 {  // not at sw corner
                 edge_pairs.push((
-                    vertices[&(current_ind - 1)].subtract(current_vert),  // s
-                    vertices[&(current_ind - res - 1)].subtract(current_vert)  // sw
+                    vertices[(current_ind - 1) as usize].subtract(current_vert),  // s
+                    vertices[(current_ind - res - 1) as usize].subtract(current_vert)  // sw
                 ));
                 edge_pairs.push((
-                    vertices[&(current_ind - res - 1)].subtract(current_vert),  // sw
-                    vertices[&(current_ind - res)].subtract(current_vert)  // w
+                    vertices[(current_ind - res - 1) as usize].subtract(current_vert),  // sw
+                    vertices[(current_ind - res) as usize].subtract(current_vert)  // w
                 ));
             }
              if i != 0 && j != res - 2 {  // not at nw corner
                 edge_pairs.push((
-                    vertices[&(current_ind - res)].subtract(current_vert),  // w
-                    vertices[&(current_ind - res + 1)].subtract(current_vert)  // nw
+                    vertices[(current_ind - res) as usize].subtract(current_vert),  // w
+                    vertices[(current_ind - res + 1) as usize].subtract(current_vert)  // nw
                 ));
                 edge_pairs.push((  // nw
-                    vertices[&(current_ind - res + 1)].subtract(current_vert),  // nw
-                    vertices[&(current_ind + 1)].subtract(current_vert)  // n
+                    vertices[(current_ind - res + 1) as usize].subtract(current_vert),  // nw
+                    vertices[(current_ind + 1) as usize].subtract(current_vert)  // n
                 ));
             }
 
-            // Note: This isn't normalized; we handle that in the shader, for now.
+            // The terrain's a surface in 4d, so it has a plane of normals at each point;
+            // use the one closest to straight up. cross4(e0, e1, up) is perpendicular to
+            // that plane's up-projected direction, and crossing again with the edges
+            // finds it.
+            let up = Vertex::new(0., 1., 0., 0.);
             let mut surrounding_norms = Vec::new();
             for (edge0, edge1) in &edge_pairs {
-                surrounding_norms.push(edge0.cross(edge1));
+                let n = edge0.cross4(edge1, &up).normal;
+                let n = edge0.cross4(edge1, &Vertex::new(n.0, n.1, n.2, n.3)).normal;
+                let sign = if n.1 < 0. { -1. } else { 1. };
+                surrounding_norms.push(unit_normal(sign * n.0, sign * n.1, sign * n.2, sign * n.3));
             }
 
             normals.push(avg_normals(surrounding_norms));
@@ -508,19 +761,128 @@ pub fn arrow(lens: (f32, f32), res: u32) -> Mesh {
     let body = spherinder(lens, res);
     let point = fivecell(lens.1 * 4.);
 
-    combine_meshes(body, vec![(point, [0., 0., 0., lens.0])])
+    combine_meshes(body, vec![(point, MeshTransform::offset([0., 0., 0., lens.0]))])
 }
 
-//pub fn make_sphere(radius: f32, res: u32) -> Mesh {
-//    assert_eq!(res % 2, 0);
-//}
+pub fn glome(radius: f32, res: u32) -> Mesh {
+    // A 3-sphere, using Hopf coordinates (η, ξ1, ξ2):
+    // (sin η cos ξ1, sin η sin ξ1, cos η cos ξ2, cos η sin ξ2), with η from 0 to
+    // τ/4, and the ξs going all the way around. The grid has res steps around each
+    // ξ, and res/4 steps of η, so its cells are close to cubes. Faces are the
+    // pieces of the coordinate surfaces: tori of constant η, and half-spheres of
+    // constant ξ1 or ξ2.
+    assert_eq!(res % 4, 0);
+
+    let m = res / 4;
+    // At η = 0, ξ1 has no effect, so those vertices only depend on ξ2. At η = τ/4,
+    // they only depend on ξ1.
+    let id = |j: u32, i: u32, k: u32| {
+        let (i, k) = (i % res, k % res);
+        if j == 0 {
+            k
+        } else if j == m {
+            res + (m - 1) * res * res + i
+        } else {
+            res + (j - 1) * res * res + i * res + k
+        }
+    };
+
+    let mut vertices = HashMap::new();
+    for j in 0..m + 1 {
+        let η = j as f32 * τ / res as f32;
+        for i in 0..res {
+            let ξ1 = i as f32 * τ / res as f32;
+            for k in 0..res {
+                let ξ2 = k as f32 * τ / res as f32;
+                vertices.insert(id(j, i, k), Vertex::new(
+                    radius * η.sin() * ξ1.cos(), radius * η.sin() * ξ1.sin(),
+                    radius * η.cos() * ξ2.cos(), radius * η.cos() * ξ2.sin(),
+                ));
+            }
+        }
+    }
+
+    let mut faces_vert = Vec::new();
+    {
+        let mut add_face = |corners: [u32; 4]| {
+            // Faces touching η = 0 or η = τ/4 lose a corner, and become triangles.
+            let mut face: Vec<u32> = Vec::new();
+            for corner in corners.iter() {
+                if face.last() != Some(corner) && face.first() != Some(corner) {
+                    face.push(*corner);
+                }
+            }
+            faces_vert.push(Array::from_vec(face));
+        };
+
+        for i in 0..res {
+            for k in 0..res {
+                for j in 0..m {
+                    if j != 0 {
+                        add_face([id(j, i, k), id(j, i + 1, k), id(j, i + 1, k + 1), id(j, i, k + 1)]);
+                    }
+                    add_face([id(j, i, k), id(j + 1, i, k), id(j + 1, i, k + 1), id(j, i, k + 1)]);
+                    add_face([id(j, i, k), id(j + 1, i, k), id(j + 1, i + 1, k), id(j, i + 1, k)]);
+                }
+            }
+        }
+    }
+
+    // The glome's centered on the origin, so normals point towards the faces' centroids.
+    let normals = faces_vert.iter().map(|face| {
+        let c = face.iter().fold((0., 0., 0., 0.), |acc, id| {
+            let p = vertices[id].position;
+            (acc.0 + p.0, acc.1 + p.1, acc.2 + p.2, acc.3 + p.3)
+        });
+        unit_normal(c.0, c.1, c.2, c.3)
+    }).collect();
+
+    Mesh::new(vertices, faces_vert, normals)
+}
+
+pub fn flat_torus(radii: (f32, f32), res: u32) -> Mesh {
+    // The torus (r0 cos a, r0 sin a, r1 cos b, r1 sin b): a circle in the x-y plane
+    // times one in the z-w plane. It's flat, and with radii of (1, 1) it's the
+    // ridge where the two cells of a duocylinder meet. Each face's normal bisects
+    // the directions straight out from the two circles, at the face's center.
+    let id = |i: u32, j: u32| (i % res) * res + j % res;
+
+    let mut vertices = HashMap::new();
+    for i in 0..res {
+        let a = i as f32 * τ / res as f32;
+        for j in 0..res {
+            let b = j as f32 * τ / res as f32;
+            vertices.insert(id(i, j), Vertex::new(
+                radii.0 * a.cos(), radii.0 * a.sin(), radii.1 * b.cos(), radii.1 * b.sin()
+            ));
+        }
+    }
+
+    let mut faces_vert = Vec::new();
+    let mut normals = Vec::new();
+    for i in 0..res {
+        let a = (i as f32 + 0.5) * τ / res as f32;
+        for j in 0..res {
+            let b = (j as f32 + 0.5) * τ / res as f32;
+            faces_vert.push(array![id(i, j), id(i + 1, j), id(i + 1, j + 1), id(i, j + 1)]);
+            normals.push(unit_normal(a.cos(), a.sin(), b.cos(), b.sin()));
+        }
+    }
+
+    Mesh::new(vertices, faces_vert, normals)
+}
+
+pub fn clifford_torus(radius: f32, res: u32) -> Mesh {
+    // The flat torus that lies on a 3-sphere, splitting it into two equal solid tori.
+    let r = radius / 2_f32.sqrt();
+    flat_torus((r, r), res)
+}
 
 pub fn twentyfourcell(radius: f32) -> Mesh {
     // Using this diagram as reference:
     // https://en.wikipedia.org/wiki/24-cell#/media/File:Schlegel_wireframe_24-cell.png
-    let coords = [
-//        // Permutations of (±1, ±1, 0, 0)
-//        // Could do this cleverly with an iteration
+    // Permutations of (±1, ±1, 0, 0)
+    let coords: [[f32; 4]; 24] = [
         // Outside
         [1., 0., 0., 1.], // 0
         [0., 1., 0., 1.],  // 1
@@ -563,348 +925,336 @@ pub fn twentyfourcell(radius: f32) -> Mesh {
         ));
     }
 
-    // Each outer and inner face has 3 vertices and 3 edges
-    // Inner and outer resemble octahedrons. Middle resembles a cuboctahedron.
-    // Middle has 8 3-vert faces.
-    // Each vertex is connected to 8 others.
-    // Ref drawing in tablet
-    // 96 faces total:
-    // 8 in inner; 8 in outer; 8 in middle
-    // 24 connecting middle squares to outside
-    // 24 connecting middle squares to inside
-    // 18 connecting middle triangles to outside.
-
-    let faces_vert = vec![
-        // Outside to itself right
-        array![0, 1, 2],
-        array![0, 2, 4],
-        array![0, 4, 5],
-        array![0, 5, 1],
-
-        // Outside to itself left
-        array![3, 1, 2],
-        array![3, 2, 4],
-        array![3, 4, 5],
-        array![3, 5, 1],
-
-        // Inside to itself right
-        array![18, 19, 20],
-        array![18, 20, 22],
-        array![18, 22, 23],
-        array![18, 23, 19],
-
-        // Inside to itself left
-        array![21, 19, 20],
-        array![21, 20, 22],
-        array![21, 22, 23],
-        array![21, 23, 19],
-
-        // Leaving this commented out chunk to prove a point: There are no square
-        // faces! the squares in the 'middle' are just a tool to organize this.
-        // The 24-cell has 96 triangular faces. Same principle as with the octahedron.
-
-//        // Middle square faces
-//        array![9, 10, 15, 16],  // Left
-//        array![6, 7, 12, 13],  // Right
-//
-//        array![9, 17, 12, 11],  // Bottom
-//        array![15, 8, 6, 14],  // Top
-//
-//        array![10, 14, 13, 11],  // Aft
-//        array![8, 7, 17, 16],  // Forward
-
-        // These triangular middle faces each have an edge belonging to a different
-        // one of the (non-face) squares above.
-        // Bottom tris
-        array![11, 13, 12],  // aft right
-        array![12, 7, 17],  // fwd right
-        array![17, 16, 9],  // fwd lrft
-        array![9, 10, 11],  // aft left
-
-        // Top tris
-        array![14, 13, 6],  // aft right
-        array![6, 7, 8],  // fwd right
-        array![8, 16, 15],  // fwd lrft
-        array![15, 10, 14],  // aft left
-
-        // Connect each of these tris to a point on the inside and outside; 24 total.
-        // Each vertex in the middle is at the intersection of two of the squares.
-        // Connect each middle vertex to the two inner and outer vertices corresponding
-        // to these squares.
-
-        // Notice the cyclic pattern below.
-        // Inner
-        array![6, 1, 0],  // top right
-        array![7, 0, 2],  // right forward
-        array![8, 2, 1 ],  // forward top
-
-        array![9, 4, 3],  // bottom left
-        array![10, 3, 5],  // left aft
-        array![11, 5, 4],  // aft bottom
-        array![12, 4, 0],  // bottom right
-        array![13, 0, 5],  // right aft
-        array![14, 5, 1],  // aft top
-        array![15, 1, 3],  // top left
-        array![16, 3, 2],  // left forward
-        array![17, 2, 4 ],  // forward  bottom
-
-        // Outer
-        array![6, 19, 18],  // top right
-        array![7, 18, 20],  // right forward
-        array![8, 20, 19 ],  // forward top
-
-        array![9, 22, 21],  // bottom left
-        array![10, 21, 23],  // left aft
-        array![11, 23, 22],  // aft bottom
-        array![12, 22, 18],  // bottom right
-        array![13, 18, 23],  // right aft
-        array![14, 23, 19],  // aft top
-        array![15, 19, 21],  // top left
-        array![16, 21, 20],  // left forward
-        array![17, 20, 22 ],  // forward  bottom
-
-        // Connect each middle square to its point on the inside and outside
-        // octahedrons, with triangular faces.  This forms new octahedrons.
-        // We make use of cyclic patterns here to simplify the logic.
-        // This uses up 48 faces; 24 connecting to inner, 24 connecting to outer.
-
-        // Left square to inner
-        array![9, 10, 3],
-        array![10, 15, 3],
-        array![15, 16, 3],
-        array![16, 9, 3],
-
-        // Left square to outer
-        array![9, 10, 21],
-        array![10, 15, 21],
-        array![15, 16, 21],
-        array![16, 9, 21],
-
-        // Right square to inner
-        array![6, 7, 0],
-        array![7, 12, 0],
-        array![12, 13, 0],
-        array![13, 6, 0],
-
-        // Right square to outer
-        array![6, 7, 18],
-        array![7, 12, 18],
-        array![12, 13, 18],
-        array![13, 6, 18],
-
-        // Bottom square to inner
-        array![9, 17, 4],
-        array![17, 12, 4],
-        array![12, 11, 4],
-        array![11, 9, 1],
-
-        // Bottom square to outer
-        array![9, 17, 22],
-        array![17, 12, 22],
-        array![12, 11, 22],
-        array![11, 9, 22],
-
-        // Top square to inner
-        array![15, 8, 1],
-        array![8, 6, 1],
-        array![6, 14, 1],
-        array![14, 15, 1],
-
-        // Top square to outer
-        array![15, 8, 19],
-        array![8, 6, 19],
-        array![6, 14, 19],
-        array![14, 15, 19],
-
-        // Aft square to inner
-        array![10, 14, 5],
-        array![14, 13, 5],
-        array![13, 11, 5],
-        array![11, 10, 5],
-
-        // Aft square to outer
-        array![10, 14, 23],
-        array![14, 13, 23],
-        array![13, 11, 23],
-        array![11, 10, 23],
-
-        // Forward square to inner
-        array![8, 7, 2],
-        array![7, 17, 2],
-        array![17, 16, 2],
-        array![16, 8, 2],
-
-        // Forward square to outer
-        array![8, 7, 20],
-        array![7, 17, 20],
-        array![17, 16, 20],
-        array![16, 8, 20],
-        // only 78 so far...
+    // The 24-cell has 24 vertices, 96 edges, 96 triangular faces and 24 octahedral
+    // cells. Each vertex is connected to the 8 others at distance √2 (in unit
+    // coordinates); every set of three mutually-connected vertices is a face.
+    // There are no square faces: the squares you see in the 'middle' layer of
+    // the Schlegel diagram are only equators of the octahedral cells.
+    let edge_len_sq = 2.;
+    let connected = |i: usize, j: usize| {
+        let dist_sq: f32 = (0..4).map(|k| (coords[i][k] - coords[j][k]).powi(2)).sum();
+        (dist_sq - edge_len_sq).abs() < 0.001
+    };
 
-    ];
+    let mut faces_vert = Vec::new();
+    for i in 0..coords.len() {
+        for j in i + 1..coords.len() {
+            if !connected(i, j) { continue }
+            for k in j + 1..coords.len() {
+                if connected(i, k) && connected(j, k) {
+                    faces_vert.push(array![i as u32, j as u32, k as u32]);
+                }
+            }
+        }
+    }
 
-//    let normals = vec![
-//        Normal::new(0., 0., 1., 0.),
-//    ];
-    let normals: Vec<Normal> = faces_vert.iter().map(|face| Normal::new(1., 0., 1., 0.)).collect();
+    // The 24-cell is centered on the origin, and each face's centroid is the point
+    // on its plane closest to the origin, so the centroid points straight out of
+    // the face.
+    let normals = faces_vert.iter().map(|face| {
+        let mut centroid = [0.; 4];
+        for vert_id in face {
+            for k in 0..4 {
+                centroid[k] += coords[*vert_id as usize][k] / 3.;
+            }
+        }
+        let len = centroid.iter().fold(0., |acc, c| acc + c * c).sqrt();
+        Normal::new(centroid[0] / len, centroid[1] / len, centroid[2] / len, centroid[3] / len)
+    }).collect();
+
+    // The octahedral cells lie on the hyperplanes v · c = 1, where c is a vertex of
+    // the dual 24-cell: Permutations of (±1, 0, 0, 0), and all of (±½, ±½, ±½, ±½).
+    let mut cell_dirs = Vec::new();
+    for i in 0..4 {
+        for sign in &[-1., 1.] {
+            let mut dir = [0.; 4];
+            dir[i] = *sign;
+            cell_dirs.push(dir);
+        }
+    }
+    for signs in 0..16 {
+        let sign = |bit: usize| if signs & (1 << bit) == 0 { -0.5 } else { 0.5 };
+        cell_dirs.push([sign(0), sign(1), sign(2), sign(3)]);
+    }
 
-    Mesh::new(vertices, faces_vert, normals)
+    let cells = cell_dirs.iter().map(|dir| {
+        let on_cell = |id: &u32| {
+            let c = coords[*id as usize];
+            (c[0] * dir[0] + c[1] * dir[1] + c[2] * dir[2] + c[3] * dir[3] - 1.).abs() < 0.001
+        };
+        (0..faces_vert.len() as u32)
+            .filter(|f| faces_vert[*f as usize].iter().all(|id| on_cell(id)))
+            .collect()
+    }).collect();
+
+    let mut result = Mesh::new(vertices, faces_vert, normals);
+    result.cells = cells;
+    result
 }
 
-pub fn spherinder(lens: (f32, f32), res: u32) -> Mesh {
-    // This is a 4d cylinder analog that extends spheres along a line in the direction
-    // not used by the spheres.
+fn even_permutations() -> [[usize; 4]; 12] {
+    // The 12 permutations of 4 items made from an even number of swaps.
+    [
+        [0, 1, 2, 3], [0, 2, 3, 1], [0, 3, 1, 2],
+        [1, 0, 3, 2], [1, 2, 0, 3], [1, 3, 2, 0],
+        [2, 0, 1, 3], [2, 1, 3, 0], [2, 3, 0, 1],
+        [3, 0, 2, 1], [3, 1, 0, 2], [3, 2, 1, 0],
+    ]
+}
 
-    // Each sphere has res longitude vertices, and res/2 latitude vertices.
+fn sixhundredcell_coords() -> Vec<[f64; 4]> {
+    // The 120 vertices of a 600-cell with circumradius 1:
+    // Permutations of (±1, 0, 0, 0), all of (±½, ±½, ±½, ±½), and even
+    // permutations of ½(±φ, ±1, ±1/φ, 0).
+    let φ = (1. + 5_f64.sqrt()) / 2.;
+    let mut result = Vec::new();
+
+    for i in 0..4 {
+        for sign in &[-1., 1.] {
+            let mut coord = [0.; 4];
+            coord[i] = *sign;
+            result.push(coord);
+        }
+    }
 
-    // We iterate over longitude twice as much as latitude, so the former must
-    // divide by 2.
-    assert_eq!(res % 2, 0);
+    for signs in 0..16 {
+        let sign = |bit: usize| if signs & (1 << bit) == 0 { -0.5 } else { 0.5 };
+        result.push([sign(0), sign(1), sign(2), sign(3)]);
+    }
 
-    // uses a crude 'UV' sphere, with uneven face sizes.  Simple algorithm, but
-    // not as smooth as other methods.  The code is similar to that used in
-    // the terrain mesh.
+    let base = [φ / 2., 0.5, 1. / (2. * φ), 0.];
+    for perm in even_permutations().iter() {
+        // The zero component doesn't take a sign.
+        for signs in 0..8 {
+            let mut coord = [0.; 4];
+            for (i, val) in base.iter().enumerate() {
+                let sign = if i < 3 && signs & (1 << i) != 0 { -1. } else { 1. };
+                coord[perm[i]] = sign * val;
+            }
+            result.push(coord);
+        }
+    }
 
-    // num vertices per sphere.  + 2 for top and bottom.
-    let svc = res * (res / 2) + 2;
+    result
+}
 
-    // todo tops and bottoms only need one vertex each, not a full set.
-    let mut vertices = HashMap::new();
-    let mut id = 0;
+fn scaled_verts(coords: &[[f64; 4]], radius: f32) -> Vec<Vertex> {
+    coords.iter().map(|c| {
+        let len = (c[0] * c[0] + c[1] * c[1] + c[2] * c[2] + c[3] * c[3]).sqrt();
+        let s = radius as f64 / len;
+        Vertex::new((c[0] * s) as f32, (c[1] * s) as f32, (c[2] * s) as f32, (c[3] * s) as f32)
+    }).collect()
+}
 
-    // We build vertices and faces for both spheres in one pass.
-    for i in 0..res {
-        // ISO standard definitions of θ and φ. The reverse is common too.
-        let φ = util::value_from_grid(i, res, (0., τ));  // longitude, 0 to τ
-        for j in 0..res / 2 {
-            let θ = util::value_from_grid(j, res, (0., τ));  // latitude, 0 to τ/2
-            // These could correlate to diff combos of x/y/z/w.
-            let a = lens.1 * θ.sin() * φ.cos();
-            let b = lens.1 * θ.sin() * φ.sin();
-            let c = lens.1 * θ.cos();
-
-            vertices.insert(id, Vertex::new(a, b, c, 0.));
-            vertices.insert(svc + id, Vertex::new(a, b, c, lens.0));
+pub fn sixhundredcell(radius: f32) -> Mesh {
+    // The 600-cell: 120 vertices, 720 edges, 1200 triangular faces and 600
+    // tetrahedral cells. radius is the distance from the center to each vertex.
+    convex_hull(&scaled_verts(&sixhundredcell_coords(), radius))
+}
 
-            id += 1;
+pub fn onehundredtwentycell(radius: f32) -> Mesh {
+    // The 120-cell: 600 vertices, 1200 edges, 720 pentagonal faces and 120
+    // dodecahedral cells. It's the dual of the 600-cell, so its vertices point
+    // towards the centers of the 600-cell's tetrahedra.
+    let coords = sixhundredcell_coords();
+    let hull = hull::convex_hull(&coords).expect("The 600-cell's vertices span 4d");
+
+    let centers: Vec<[f64; 4]> = hull.cells.iter().map(|cell| {
+        let n = cell.verts.len() as f64;
+        let mut center = [0.; 4];
+        for v in &cell.verts {
+            for k in 0..4 {
+                center[k] += coords[*v][k] / n;
+            }
         }
-    }
+        center
+    }).collect();
 
-    // Add top and bottom vertices.
-    vertices.insert(id, Vertex::new(0., lens.1, 0., 0.));
-    vertices.insert(id + svc, Vertex::new(0., lens.1, 0., 0.));
-    id += 1;
-    vertices.insert(id, Vertex::new(0., -lens.1, 0., 0.));
-    vertices.insert(id + svc, Vertex::new(0., -lens.1, 0., 0.));
+    convex_hull(&scaled_verts(&centers, radius))
+}
 
-    let mut faces_vert = Vec::new();
-    let mut lon_i = 0;
-    let mut normals = Vec::new();
+fn coxeter_matrix(group: CoxeterGroup) -> [[u32; 4]; 4] {
+    // m[i][j] is the order of the rotation made by reflecting in mirrors i and j;
+    // 2 (perpendicular mirrors) for nodes that aren't connected in the diagram.
+    let (m01, m12, m23, m13) = match group {
+        CoxeterGroup::A4 => (3, 3, 3, 2),
+        CoxeterGroup::B4 => (4, 3, 3, 2),
+        CoxeterGroup::D4 => (3, 3, 2, 3),
+        CoxeterGroup::F4 => (3, 4, 3, 2),
+        CoxeterGroup::H4 => (5, 3, 3, 2),
+    };
+
+    [
+        [1, m01, 2, 2],
+        [m01, 1, m12, m13],
+        [2, m12, 1, m23],
+        [2, m13, m23, 1],
+    ]
+}
 
-    // These are four-sided faces; let Shape.make_tris divide them.  There are
-    // res-1 lon faces, and res/2 - 1 lat faces, plus faces 2x res/2 - 1 connecting to the
-    // top and bottom. (Not including the faces connecting the two spheres).
-    for i in 0..res {
-        let mut lon_adjuster = 0;
-        if i == res - 1 {
-            // Num lat vertices * num lon verts between this and origin,
-            // - the original lon adjustor of res/2.
-            lon_adjuster = res/2 * (res - 1) - res/2;
-        }
-
-        for j in 0..res/2 - 1 {
-            // We increment lat with j. We increment lon with res/2.
-            // When at the last longitude, we wrap to the beginning.  This factor
-            // is required for the lon adjusted points.
-
-            for sp_selector in &[0, svc] {
-                faces_vert.push(
-                    array![
-                        sp_selector + lon_i + j,  // current point
-                        sp_selector + lon_i + j + 1,  // up one lat
-                        sp_selector + lon_i + j + res/2 + 1 - lon_adjuster,  // up one lat and one lon
-                        sp_selector + lon_i + j + res/2 - lon_adjuster,  // up one lon
-                    ]
-                );
-                // We're ignoring w in normals, for now.
-                let line1 = vertices[&(sp_selector + lon_i + j)].subtract(
-                    &vertices[&(sp_selector + lon_i + j + res/2 - lon_adjuster)]);
-                let line2 = vertices[&(sp_selector + lon_i + j + res/2 + 1 - lon_adjuster)].subtract(
-                    &vertices[&(sp_selector + lon_i + j)]);
-                normals.push(line1.cross(&line2));
+fn mirror_normals(m: &[[u32; 4]; 4]) -> [[f64; 4]; 4] {
+    // Find unit normals for the four mirrors, with the angle between mirrors
+    // i and j being π / m[i][j]. We take the Cholesky decomposition of the Gram
+    // matrix of the normals, G[i][j] = -cos(π / m[i][j]); the rows of the result
+    // are the normals.
+    let gram = |i: usize, j: usize| if i == j { 1. } else { -(std::f64::consts::PI / m[i][j] as f64).cos() };
+
+    let mut normals = [[0.; 4]; 4];
+    for i in 0..4 {
+        for j in 0..i + 1 {
+            let sum: f64 = (0..j).map(|k| normals[i][k] * normals[j][k]).sum();
+            if i == j {
+                normals[i][i] = (gram(i, i) - sum).sqrt();
+            } else {
+                normals[i][j] = (gram(i, j) - sum) / normals[j][j];
             }
+        }
+    }
+    normals
+}
 
-            // Add caps if at the beginning or end or lat iteration.
-            if j == 0 {  // Bottom cap
-                for sp_selector in &[0, svc] {
-                    faces_vert.push(  // origin sphere
-                        array![
-                            sp_selector + lon_i + j,  // current point
-                            sp_selector + lon_i + j + res/2 - lon_adjuster,  // up one lon
-                            sp_selector + svc - 1  // Bottom point
-                        ]
-                    );
-                    let line1 = vertices[&(sp_selector + lon_i + j)].subtract(
-                        &vertices[&(sp_selector + lon_i + j + res/2 - lon_adjuster)]);
-                    let line2 = vertices[&(sp_selector + svc - 1)].subtract(
-                        &vertices[&(sp_selector + lon_i + j)]);
-                    normals.push(line1.cross(&line2));
-                }
-            } else if j == res/2 - 2{  // Top cap
+fn reflect(p: &[f64; 4], normal: &[f64; 4]) -> [f64; 4] {
+    let d = 2. * (0..4).map(|k| p[k] * normal[k]).sum::<f64>();
+    [p[0] - d * normal[0], p[1] - d * normal[1], p[2] - d * normal[2], p[3] - d * normal[3]]
+}
 
-            }
+pub fn wythoff(group: CoxeterGroup, ringed: [bool; 4]) -> (Mesh, Vec<FaceGroup>) {
+    // Build a uniform 4-polytope using the Wythoff construction, eg
+    // (B4, [true, false, false, false]) is the tesseract, and
+    // (B4, [true, true, false, false]) the truncated tesseract. All edges have
+    // length 1. Faces are sorted so each group's faces are next to each other.
+    if !ringed.iter().any(|r| *r) {
+        panic!("At least one node of the Coxeter diagram must be ringed.")
+    }
 
-            faces_vert.push(  // origin sphere to end sphere along lat
-                array![
-                    lon_i + j,
-                    lon_i + j + 1,
-                    svc + lon_i + j,
-                    svc + lon_i + j + 1,
-                ]
-            );
-            faces_vert.push(  // origin sphere to end sphere along lon
-                array![
-                    lon_i + j + res/2 + 1 - lon_adjuster,
-                    lon_i + j + res/2 - lon_adjuster,
-                    svc + lon_i + j + res/2 + 1 - lon_adjuster,
-                    svc + lon_i + j + res/2 - lon_adjuster,
-                ]
-            );
+    let m = coxeter_matrix(group);
+    let normals = mirror_normals(&m);
+
+    // The seed point lies on every unringed mirror, and half an edge length
+    // from every ringed one. The normals matrix is lower-triangular, so solve by
+    // forward substitution.
+    let mut seed = [0.; 4];
+    for i in 0..4 {
+        let target = if ringed[i] { 0.5 } else { 0. };
+        let sum: f64 = (0..i).map(|k| normals[i][k] * seed[k]).sum();
+        seed[i] = (target - sum) / normals[i][i];
+    }
 
-//            faces_vert.push(  // origin sphere to end sphere part 3  ??
-//                array![
-//                    lon_i + j,
-//                    lon_i + res/2 - lon_adjuster,
-//                    svc + lon_i + j,
-//                    svc + lon_i + res/2 - lon_adjuster,
-//                ]
-//            );
-//            faces_vert.push(  // origin sphere to end sphere part 4 ??
-//                array![
-//                    lon_i + j + 1,
-//                    lon_i + j + res/2 + 1 - lon_adjuster,
-//                    svc + lon_i + j + 1,
-//                    svc + lon_i + j + res/2 + 1 - lon_adjuster,
-//                ]
-//            );
+    // The polytope's vertices are the orbit of the seed under the group's reflections.
+    let key = |p: &[f64; 4]| [(p[0] * 1e4).round() as i64, (p[1] * 1e4).round() as i64,
+                              (p[2] * 1e4).round() as i64, (p[3] * 1e4).round() as i64];
+    let mut seen = HashMap::new();
+    seen.insert(key(&seed), ());
+    let mut orbit = vec![seed];
+    let mut i = 0;
+    while i < orbit.len() {
+        for normal in &normals {
+            let reflected = reflect(&orbit[i], normal);
+            if !seen.contains_key(&key(&reflected)) {
+                seen.insert(key(&reflected), ());
+                orbit.push(reflected);
+            }
+        }
+        i += 1;
+    }
 
-            // todo I suspect we're missing some faces.
+    let points: Vec<Vertex> = orbit.iter()
+        .map(|p| Vertex::new(p[0] as f32, p[1] as f32, p[2] as f32, p[3] as f32))
+        .collect();
+    let mesh = convex_hull(&points);
+
+    // Each pair of mirrors with at least one ringed node makes a type of face:
+    // The orbit of the seed under those two reflections. Its centroid is the seed
+    // with the component in the plane of the two mirrors' normals removed.
+    let mut types: Vec<(FaceGroup, [f64; 4])> = Vec::new();
+    for i in 0..4 {
+        for j in i + 1..4 {
+            let sides = match (ringed[i], ringed[j]) {
+                (true, true) => 2 * m[i][j] as usize,
+                (false, false) => continue,
+                _ if m[i][j] == 2 => continue,  // Only makes an edge.
+                _ => m[i][j] as usize,
+            };
+
+            // Orthonormal basis for the plane of normals i and j.
+            let (ni, nj) = (normals[i], normals[j]);
+            let d_ij: f64 = (0..4).map(|k| ni[k] * nj[k]).sum();
+            let mut u = [0.; 4];
+            for k in 0..4 {
+                u[k] = nj[k] - d_ij * ni[k];
+            }
+            let u_len = u.iter().map(|c| c * c).sum::<f64>().sqrt();
 
+            let a: f64 = (0..4).map(|k| seed[k] * ni[k]).sum();
+            let b: f64 = (0..4).map(|k| seed[k] * u[k] / u_len).sum();
+            let mut centroid = [0.; 4];
+            for k in 0..4 {
+                centroid[k] = seed[k] - a * ni[k] - b * u[k] / u_len;
+            }
 
-            // todo temp for faces connecting the spheres.
-            normals.push(Normal::new(0., 0., 0., 0.));
-            normals.push(Normal::new(0., 0., 0., 0.));
-//            normals.push(Normal::new(0., 0., 0., 0.));
-//            normals.push(Normal::new(0., 0., 0., 0.));
+            types.push((FaceGroup { generators: (i, j), sides, faces: Vec::new() }, centroid));
+        }
+    }
+
+    // Every face is the image of one of these under the group. To find which,
+    // reflect the face's centroid back into the fundamental region (the side of
+    // every mirror the seed is on), and compare.
+    for (face_id, face) in mesh.faces_vert.iter().enumerate() {
+        let n = face.len() as f64;
+        let mut centroid = [0.; 4];
+        for vert_id in face {
+            let p = mesh.vertices[*vert_id as usize].position;
+            centroid = [centroid[0] + p.0 as f64 / n, centroid[1] + p.1 as f64 / n,
+                        centroid[2] + p.2 as f64 / n, centroid[3] + p.3 as f64 / n];
+        }
 
+        loop {
+            let outside = normals.iter()
+                .find(|normal| (0..4).map(|k| centroid[k] * normal[k]).sum::<f64>() < -1e-5);
+            match outside {
+                Some(normal) => centroid = reflect(&centroid, normal),
+                None => break,
+            }
         }
 
-        lon_i += res / 2;
+        let dist = |c: &[f64; 4]| (0..4).map(|k| (c[k] - centroid[k]).powi(2)).sum::<f64>();
+        let type_ = types.iter_mut()
+            .filter(|(group, _)| group.sides == face.len())
+            .min_by(|a, b| dist(&a.1).partial_cmp(&dist(&b.1)).unwrap())
+            .expect("Face doesn't match any face type of the Wythoff construction.");
+        type_.0.faces.push(face_id);
     }
 
-    // Add the faces spanning the space between the two spheres.
+    // Re-order the faces so each group is contiguous.
+    let mut faces_vert = Vec::new();
+    let mut face_normals = Vec::new();
+    let mut new_face_ids = vec![0; mesh.faces_vert.len()];
+    let mut groups = Vec::new();
+    for (mut group, _) in types.into_iter() {
+        if group.faces.is_empty() { continue }
+        let mut new_ids = Vec::new();
+        for face_id in &group.faces {
+            new_face_ids[*face_id] = faces_vert.len() as u32;
+            new_ids.push(faces_vert.len());
+            faces_vert.push(mesh.faces_vert[*face_id].clone());
+            face_normals.push(mesh.normals[*face_id]);
+        }
+        group.faces = new_ids;
+        groups.push(group);
+    }
 
-    Mesh::new(vertices, faces_vert, normals)
+    let mut result = Mesh::new(mesh.vertices, faces_vert, face_normals);
+    result.cells = mesh.cells.iter()
+        .map(|cell| cell.iter().map(|f| new_face_ids[*f as usize]).collect())
+        .collect();
+    (result, groups)
+}
+
+pub fn spherinder(lens: (f32, f32), res: u32) -> Mesh {
+    // This is a 4d cylinder analog that extends spheres along a line in the direction
+    // not used by the spheres. lens is (length, radius). Each sphere has res
+    // longitude vertices, and res/2 latitude vertices.
+    prism_4d(sphere(lens.1, res), lens.0)
 }
 
 
@@ -914,51 +1264,474 @@ pub fn letter_cube(len: f32, letter: &str) {
 
 
 pub fn origin(lens: (f32, f32), res: u32) -> Mesh {
+    // Arrows pointing along each axis. Each arrow starts along w, so rotate it by
+    // τ/4 in the plane between w and its axis.
+    let parts = vec![
+        (arrow(lens, res), MeshTransform::new(array![0., 0., 0., τ/4., 0., 0.], 1., [0.; 4])),  // x
+        (arrow(lens, res), MeshTransform::new(array![0., 0., 0., 0., τ/4., 0.], 1., [0.; 4])),  // y
+        (arrow(lens, res), MeshTransform::new(array![0., 0., 0., 0., 0., τ/4.], 1., [0.; 4])),  // z
+    ];
 
-    let mut w = arrow(lens, res);
-    let x = arrow(lens, res);
-    let y = arrow(lens, res);
-    let z = arrow(lens, res);
+    // The arrows' tails overlap at the origin; merge their shared vertices.
+    let mut result = combine_meshes(arrow(lens, res), parts);
+    result.weld(1e-5 * lens.0.max(lens.1));
+    result
+}
 
-    let param_set = vec![
-        (w.vertices.len() as u32, array![0., 0., 0., τ/4., 0., 0.], x),
-        (2 * w.vertices.len() as u32, array![0., 0., 0., 0., τ/4., 0.], y),
-        (3 * w.vertices.len() as u32, array![0., 0., 0., 0., 0., τ/4.], z),
-    ];
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cube() {
 
-    for (id_addition, θ, shape) in &param_set {
-        let R = transforms::make_rotator4(&θ);
-        for (id, vertex) in &shape.vertices {
-            // For the roof, modify the ids to be unique.
-            let rotated_vert = transforms::dot_mv4(R, [vertex.position.0,
-                vertex.position.1, vertex.position.2, vertex.position.3]);
-            w.vertices.insert(
-                id + id_addition,
-                Vertex::new(rotated_vert[0], rotated_vert[1], rotated_vert[2], rotated_vert[3]));
+    }
 
+    #[test]
+    fn test_convex_hull() {
+        // The corners of a tesseract, plus some points inside it and on its
+        // boundary that shouldn't become vertices.
+        let mut points = Vec::new();
+        for i in 0..16 {
+            points.push(Vertex::new(
+                if i & 1 == 0 { -1. } else { 1. }, if i & 2 == 0 { -1. } else { 1. },
+                if i & 4 == 0 { -1. } else { 1. }, if i & 8 == 0 { -1. } else { 1. },
+            ));
+        }
+        points.push(Vertex::new(0., 0., 0., 0.));
+        points.push(Vertex::new(0.2, -0.5, 0.1, 0.7));
+        points.push(Vertex::new(1., 0., 0., 0.));  // Center of a cell
+        points.push(Vertex::new(1., 1., 0., 0.));  // Center of a face
+
+        let mesh = convex_hull(&points);
+        assert_eq!(mesh.vertices.len(), 16);
+        assert_eq!(mesh.edges().len(), 32);
+        assert_eq!(mesh.faces_vert.len(), 24);
+
+        for (face, normal) in mesh.faces_vert.iter().zip(&mesh.normals) {
+            assert_eq!(face.len(), 4);
+            // Consecutive vertices of each square are joined by an edge of length 2.
+            for i in 0..4 {
+                let edge = mesh.vertices[face[(i + 1) % 4] as usize].subtract(&mesh.vertices[face[i] as usize]);
+                let p = edge.position;
+                assert!((p.0 * p.0 + p.1 * p.1 + p.2 * p.2 + p.3 * p.3 - 4.).abs() < 1e-5);
+            }
+            // Face normals of a tesseract point along the diagonal of two axes.
+            let n = normal.normal;
+            let mut comps = [n.0.abs(), n.1.abs(), n.2.abs(), n.3.abs()];
+            comps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert!(comps[0] < 1e-5 && comps[1] < 1e-5);
+            assert!((comps[2] - 0.5_f32.sqrt()).abs() < 1e-5);
         }
+    }
 
-        for face in &shape.faces_vert {
-            w.faces_vert.push(face + *id_addition);
+    #[test]
+    fn test_convex_hull_edge_midpoints() {
+        // Four cells meet at each edge of the 16-cell, so its edge midpoints touch
+        // four cells without being vertices. They go first, so they're part of the
+        // triangulation before the vertices are added.
+        let mut corners = Vec::new();
+        for i in 0..4 {
+            for sign in &[-1., 1.] {
+                let mut p = [0.; 4];
+                p[i] = *sign;
+                corners.push(Vertex::new(p[0], p[1], p[2], p[3]));
+            }
         }
+        let mut points = Vec::new();
+        for a in 0..8 {
+            for b in a + 1..8 {
+                if a / 2 == b / 2 { continue }  // Opposite vertices aren't joined.
+                let mid = corners[a].add(&corners[b]).position;
+                points.push(Vertex::new(mid.0 / 2., mid.1 / 2., mid.2 / 2., mid.3 / 2.));
+            }
+        }
+        points.extend(corners);
+        assert_eq!(points.len(), 24 + 8);
+
+        let mesh = convex_hull(&points);
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.edges().len(), 24);
+        assert_eq!(mesh.faces_vert.len(), 32);
+        assert!(mesh.faces_vert.iter().all(|face| face.len() == 3));
+    }
 
-        for normal in &shape.normals {  // todo rotate normals!
-            w.normals.push(normal.clone());
+    #[test]
+    fn test_convex_hull_errors() {
+        assert_eq!(try_convex_hull(&[]).unwrap_err(), HullError::TooFewPoints);
+        let axes = [Vertex::new(1., 0., 0., 0.), Vertex::new(0., 1., 0., 0.),
+                    Vertex::new(0., 0., 1., 0.), Vertex::new(0., 0., 0., 1.)];
+        assert_eq!(try_convex_hull(&axes).unwrap_err(), HullError::TooFewPoints);
+
+        let same = vec![Vertex::new(0., 0., 0., 0.); 6];
+        assert_eq!(try_convex_hull(&same).unwrap_err(), HullError::Flat);
+        let same = vec![Vertex::new(1., 2., 3., 4.); 6];
+        assert_eq!(try_convex_hull(&same).unwrap_err(), HullError::Flat);
+
+        // A cube, with a constant w.
+        let cube: Vec<Vertex> = (0..8).map(|i| Vertex::new(
+            (i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2 & 1) as f32, 0.5)).collect();
+        assert_eq!(try_convex_hull(&cube).unwrap_err(), HullError::Flat);
+
+        let mut broken = cube.clone();
+        broken.push(Vertex::new(0., 0., 0., 1.));
+        assert!(try_convex_hull(&broken).is_ok());
+        broken[3] = Vertex::new(0., ::std::f32::NAN, 0., 0.);
+        assert_eq!(try_convex_hull(&broken).unwrap_err(), HullError::NonFinitePoint(3));
+    }
+
+    fn assert_counts(mesh: &Mesh, v: usize, e: usize, f: usize, face_len: usize) {
+        assert_eq!(mesh.vertices.len(), v);
+        assert_eq!(mesh.edges().len(), e);
+        assert_eq!(mesh.faces_vert.len(), f);
+        assert!(mesh.faces_vert.iter().all(|face| face.len() == face_len));
+    }
+
+    #[test]
+    fn test_indexed_sizes() {
+        // With flat normals, a tesseract's vertices are split between its faces;
+        // smooth normals share them.
+        let tesseract = make_hypercube(1.);
+        assert_eq!(tesseract.to_indexed(false).unwrap().vertices.len(), 96);
+        assert_eq!(tesseract.to_indexed(true).unwrap().vertices.len(), 16);
+
+        // Likewise for bumpy terrain.
+        let res = 10;
+        let heights = Array2::from_shape_fn((res, res), |(i, j)| ((i * j) as f32).sin());
+        let spiss = Array2::from_shape_fn((res, res), |(i, j)| ((i + 2 * j) as f32).cos());
+        let ground = terrain((5., 5.), res as u32, heights, spiss);
+        let corners = 4 * (res - 1) * (res - 1);
+        assert_eq!(ground.to_indexed(false).unwrap().vertices.len(), corners);
+        assert_eq!(ground.to_indexed(true).unwrap().vertices.len(), res * res);
+    }
+
+    #[test]
+    fn test_terrain() {
+        // A ramp, sloping up in y and w along x.
+        let res = 6;
+        let heights = Array2::from_shape_fn((res, res), |(i, _)| i as f32 * 0.4);
+        let spiss = Array2::from_shape_fn((res, res), |(i, _)| i as f32 * -0.7);
+        let mesh = terrain((5., 5.), res as u32, heights, spiss);
+
+        // Normals are perpendicular to the ramp, and as close to up as they can be:
+        // up, minus its component along the slope.
+        let along_x = Vertex::new(5. / res as f32, 0.4, 0., -0.7);
+        let along_z = Vertex::new(0., 0., 1., 0.);
+        let up = Vertex::new(0., 1., 0., 0.);
+        let expected = up.subtract(&along_x.scale(0.4 / along_x.dot(&along_x)));
+        let expected = expected.scale(1. / expected.dot(&expected).sqrt());
+
+        for normal in &mesh.normals {
+            let n = normal.normal;
+            let n = Vertex::new(n.0, n.1, n.2, n.3);
+            assert!(n.dot(&along_x).abs() < 1e-5);
+            assert!(n.dot(&along_z).abs() < 1e-5);
+            assert!((n.dot(&expected) - 1.).abs() < 1e-5);
         }
+    }
 
+    #[test]
+    fn test_prism_4d() {
+        let tesseract = prism_4d(cube(2.), 2.);
+        assert_counts(&tesseract, 16, 32, 24, 4);
+        assert!(tesseract.vertices.iter().all(|v| v.position.3 == 0. || v.position.3 == 2.));
+
+        // Every face of a tesseract lies on two cells whose normals are along
+        // different axes.
+        for normal in &tesseract.normals {
+            let n = normal.normal;
+            let mut comps = [n.0.abs(), n.1.abs(), n.2.abs(), n.3.abs()];
+            comps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert!(comps[0] < 1e-5 && comps[1] < 1e-5);
+            assert!((comps[2] - 0.5_f32.sqrt()).abs() < 1e-5);
+        }
+
+        let hyperrect = hyperrect((1., 2., 3., 4.));
+        assert_counts(&hyperrect, 16, 32, 24, 4);
+        assert!(hyperrect.vertices.iter().all(|v| v.position.3.abs() == 2.));
+
+        // A house has 13 vertices, 20 edges and 11 faces. Each vertex sweeps out
+        // an edge, and each edge a face.
+        let house_prism = prism_4d(house((1., 1., 1.)), 1.);
+        assert_eq!(house_prism.vertices.len(), 26);
+        assert_eq!(house_prism.edges().len(), 2 * 20 + 13);
+        assert_eq!(house_prism.faces_vert.len(), 2 * 11 + 20);
     }
 
-    w.make_tris();
+    #[test]
+    fn test_sphere() {
+        let mesh = sphere(2., 8);
+        assert_eq!(mesh.vertices.len(), 3 * 8 + 2);
+        assert_eq!(mesh.faces_vert.len(), 4 * 8);
+        assert!(mesh.vertices.iter().all(|v| {
+            let p = v.position;
+            (p.0 * p.0 + p.1 * p.1 + p.2 * p.2 - 4.).abs() < 1e-5
+        }));
+        // Every edge is shared by two faces.
+        assert!(edge_faces(&mesh).iter().all(|(_, faces)| faces.len() == 2));
+    }
 
-    w
-}
+    #[test]
+    fn test_spherinder() {
+        let (len, res) = (3., 8);
+        let base = sphere(0.5, res);
+        let mesh = spherinder((len, 0.5), res);
+        let n = base.vertices.len() as u32;
+        assert_eq!(mesh.vertices.len() as u32, 2 * n);
+
+        // In 4d, the edges around each cap touch three faces: two on the cap, and
+        // one joining the caps. The mesh is closed if every cell is: each edge of a
+        // cell must border exactly two of the cell's faces.
+        let assert_closed = |cell: Vec<&Array1<u32>>| {
+            let mut edge_counts = HashMap::new();
+            for face in &cell {
+                for i in 0..face.len() {
+                    let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                    *edge_counts.entry(if a < b { (a, b) } else { (b, a) }).or_insert(0) += 1;
+                }
+            }
+            assert!(edge_counts.values().all(|count| *count == 2));
+        };
+
+        // The two caps.
+        for w in &[0., len] {
+            assert_closed(mesh.faces_vert.iter()
+                .filter(|face| face.iter().all(|v| mesh.vertices[*v as usize].position.3 == *w))
+                .collect());
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        // The sides: each face of the sphere sweeps out a cell.
+        for base_face in &base.faces_vert {
+            let cell: Vec<&Array1<u32>> = mesh.faces_vert.iter()
+                .filter(|face| face.iter().all(|v| base_face.iter().any(|b| *b == v % n)))
+                .collect();
+            assert_eq!(cell.len(), base_face.len() + 2);
+            assert_closed(cell);
+        }
+
+        for normal in &mesh.normals {
+            let n = normal.normal;
+            assert!((n.0 * n.0 + n.1 * n.1 + n.2 * n.2 + n.3 * n.3 - 1.).abs() < 1e-5);
+        }
+    }
 
     #[test]
-    fn test_cube() {
+    fn test_glome() {
+        let res = 8;
+        let mesh = glome(2., res);
+        let (res, m) = (res as usize, res as usize / 4);
+
+        assert_eq!(mesh.vertices.len(), 2 * res + (m - 1) * res * res);
+        assert_eq!(mesh.faces_vert.len(), (3 * m - 1) * res * res);
+        assert_eq!(mesh.faces_vert.iter().filter(|f| f.len() == 3).count(), 2 * res * res);
+        assert!(mesh.vertices.iter().all(|v| {
+            let p = v.position;
+            (p.0 * p.0 + p.1 * p.1 + p.2 * p.2 + p.3 * p.3 - 4.).abs() < 1e-4
+        }));
+
+        // The 3-sphere's Euler characteristic is 0, so V - E + F equals the number
+        // of cells: one per grid step of (η, ξ1, ξ2).
+        let euler = mesh.vertices.len() + mesh.faces_vert.len() - mesh.edges().len();
+        assert_eq!(euler, m * res * res);
+    }
+
+    #[test]
+    fn test_clifford_torus() {
+        let mesh = clifford_torus(2., 12);
+        assert_eq!(mesh.vertices.len(), 144);
+        assert_eq!(mesh.faces_vert.len(), 144);
+        // A torus is a closed surface, even in 4d.
+        assert!(edge_faces(&mesh).iter().all(|(_, faces)| faces.len() == 2));
+
+        assert!(mesh.vertices.iter().all(|v| {
+            let p = v.position;
+            (p.0 * p.0 + p.1 * p.1 + p.2 * p.2 + p.3 * p.3 - 4.).abs() < 1e-4
+        }));
+
+        assert_normals_perpendicular(&mesh);
+    }
 
+    #[test]
+    fn test_pyramid_4d() {
+        // A pyramid over a cube has 9 vertices, 12 + 8 edges and 6 + 12 faces.
+        let mesh = pyramid_4d(cube(2.), 1.5);
+        assert_eq!(mesh.vertices.len(), 9);
+        assert_eq!(mesh.edges().len(), 20);
+        assert_eq!(mesh.faces_vert.len(), 18);
+
+        assert_normals_perpendicular(&mesh);
+
+        // Faces of the base lean away from the apex, on either side.
+        for &apex_w in &[1.5, -1.5] {
+            let mesh = pyramid_4d(cube(2.), apex_w);
+            assert!(mesh.normals[..6].iter().all(|n| n.normal.3 * apex_w < 0.));
+        }
+
+        assert_counts(&fivecell(2.), 5, 10, 10, 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pyramid_4d_flat() {
+        pyramid_4d(cube(2.), 0.);
+    }
+
+    #[test]
+    fn test_polygon_product() {
+        assert_counts(&duoprism(4, 4, 1.), 16, 32, 24, 4);
+
+        // A 3,5-duoprism has 15 squares, 5 triangles and 3 pentagons.
+        let mesh = duoprism(3, 5, 1.);
+        assert_eq!(mesh.vertices.len(), 15);
+        assert_eq!(mesh.edges().len(), 30);
+        assert_eq!(mesh.faces_vert.len(), 15 + 5 + 3);
+        assert_eq!(mesh.faces_vert.iter().filter(|f| f.len() == 3).count(), 5);
+        assert_eq!(mesh.faces_vert.iter().filter(|f| f.len() == 5).count(), 3);
+
+        // Clockwise polygons give the same normals.
+        let mut cw = polygon(3, 1.);
+        cw.reverse();
+        let flipped = polygon_product(&cw, &polygon(5, 1.));
+        for (face, normal) in flipped.faces_vert.iter().zip(&flipped.normals) {
+            let n = normal.normal;
+            let c = face.iter().fold((0., 0., 0., 0.), |acc, id| {
+                let p = flipped.vertices[*id as usize].position;
+                (acc.0 + p.0, acc.1 + p.1, acc.2 + p.2, acc.3 + p.3)
+            });
+            // The duoprism's centered on the origin, so normals point away from it.
+            assert!(n.0 * c.0 + n.1 * c.1 + n.2 * c.2 + n.3 * c.3 > 0.);
+        }
+    }
+
+    fn assert_normals_perpendicular(mesh: &Mesh) {
+        for (face, normal) in mesh.faces_vert.iter().zip(&mesh.normals) {
+            let n = normal.normal;
+            let n = Vertex::new(n.0, n.1, n.2, n.3);
+            for i in 0..face.len() {
+                let edge = mesh.vertices[face[(i + 1) % face.len()] as usize].subtract(&mesh.vertices[face[i] as usize]);
+                assert!(n.dot(&edge).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_combine_meshes() {
+        let part = MeshTransform::new(array![0.3, -1., 0.2, 0.7, 0.5, -0.4], -2., [1., 2., 3., 4.]);
+        let mesh = combine_meshes(cube(1.), vec![(cube(1.), part)]);
+        assert_eq!(mesh.vertices.len(), 16);
+        assert_eq!(mesh.faces_vert.len(), 12);
+        assert_normals_perpendicular(&mesh);
+
+        // Normals of the moved cube still point away from its center.
+        for (face, normal) in mesh.faces_vert[6..].iter().zip(&mesh.normals[6..]) {
+            let n = normal.normal;
+            let p = mesh.vertices[face[0] as usize].position;
+            let out = Vertex::new(p.0 - 1., p.1 - 2., p.2 - 3., p.3 - 4.);
+            assert!(out.dot(&Vertex::new(n.0, n.1, n.2, n.3)) > 0.);
+        }
+
+        // The x, y and z arrows have normals of their own.
+        let origin = origin((1., 0.1), 8);
+        assert_normals_perpendicular(&origin);
+    }
+
+    fn assert_cells(mesh: &Mesh, num_cells: usize, faces_per_cell: usize) {
+        // Check a polytope's cells are closed, and join up with each other.
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.cells.len(), num_cells);
+        assert!(mesh.cells.iter().all(|cell| cell.len() == faces_per_cell));
+        for face in 0..mesh.faces_vert.len() {
+            assert_eq!(mesh.cells_of_face(face).len(), 2);
+        }
+        for cell in 0..num_cells {
+            assert_eq!(mesh.cell_neighbors(cell).len(), faces_per_cell);
+        }
+    }
+
+    #[test]
+    fn test_cells() {
+        assert_cells(&make_hypercube(1.), 8, 6);
+        assert_cells(&fivecell(1.), 5, 4);
+        assert_cells(&twentyfourcell(1.), 24, 8);
+        assert_cells(&sixteencell(1.), 16, 4);
+        assert_cells(&wythoff(CoxeterGroup::A4, [true, false, false, false]).0, 5, 4);
+
+        // A spherinder's cells are the two spheres, and a prism per face of the spheres.
+        let mesh = spherinder((1., 1.), 8);
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.cells.len(), 2 + 4 * 8);
+    }
+
+    #[test]
+    fn test_sixteencell() {
+        assert_counts(&sixteencell(1.), 8, 24, 32, 3);
+    }
+
+    #[test]
+    fn test_sixhundredcell_onehundredtwentycell() {
+        assert_counts(&sixhundredcell(1.), 120, 720, 1200, 3);
+        assert_counts(&onehundredtwentycell(1.), 600, 1200, 720, 5);
+    }
+
+    #[test]
+    fn test_wythoff() {
+        let (tesseract, groups) = wythoff(CoxeterGroup::B4, [true, false, false, false]);
+        assert_counts(&tesseract, 16, 32, 24, 4);
+        assert_eq!(groups.len(), 1);
+
+        assert_counts(&wythoff(CoxeterGroup::B4, [false, false, false, true]).0, 8, 24, 32, 3);
+        assert_counts(&wythoff(CoxeterGroup::A4, [true, false, false, false]).0, 5, 10, 10, 3);
+        assert_counts(&wythoff(CoxeterGroup::D4, [true, false, false, false]).0, 8, 24, 32, 3);
+        assert_counts(&wythoff(CoxeterGroup::F4, [true, false, false, false]).0, 24, 96, 96, 3);
+        assert_counts(&wythoff(CoxeterGroup::H4, [false, false, false, true]).0, 120, 720, 1200, 3);
+
+        // Truncated tesseract: 24 octagons and 64 triangles.
+        let (mesh, groups) = wythoff(CoxeterGroup::B4, [true, true, false, false]);
+        assert_eq!(mesh.vertices.len(), 64);
+        let counts: Vec<(usize, usize)> = groups.iter().map(|g| (g.sides, g.faces.len())).collect();
+        assert_eq!(counts, vec![(8, 24), (3, 64)]);
+
+        // Runcinated tesseract: The squares of its cubes and of its prisms are
+        // different types.
+        let (mesh, groups) = wythoff(CoxeterGroup::B4, [true, false, false, true]);
+        assert_eq!(mesh.faces_vert.len(), 208);
+        let counts: Vec<((usize, usize), usize)> = groups.iter().map(|g| (g.generators, g.faces.len())).collect();
+        assert_eq!(counts, vec![((0, 1), 48), ((0, 3), 96), ((2, 3), 64)]);
+        for group in &groups {
+            for face in &group.faces {
+                assert_eq!(mesh.faces_vert[*face].len(), group.sides);
+            }
+        }
+
+        // All edges have length 1.
+        for face in &mesh.faces_vert {
+            for i in 0..face.len() {
+                let p = mesh.vertices[face[(i + 1) % face.len()] as usize].subtract(&mesh.vertices[face[i] as usize]).position;
+                assert!((p.0 * p.0 + p.1 * p.1 + p.2 * p.2 + p.3 * p.3 - 1.).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_twentyfourcell() {
+        let mesh = twentyfourcell(2.);
+
+        let (v, e, f) = (mesh.vertices.len() as i32, mesh.edges().len() as i32,
+                         mesh.faces_vert.len() as i32);
+        assert_eq!(v, 24);
+        assert_eq!(e, 96);
+        assert_eq!(f, 96);
+        // Euler characteristic of a 4-polytope's boundary: V - E + F - C = 0.
+        assert_eq!(v - e + f, 24);
+
+        assert_eq!(mesh.normals.len(), mesh.faces_vert.len());
+        assert_normals_perpendicular(&mesh);
+        for (face, normal) in mesh.faces_vert.iter().zip(&mesh.normals) {
+            assert_eq!(face.len(), 3);
+            // Normals point away from the center.
+            let n = normal.normal;
+            let p = mesh.vertices[face[0] as usize].position;
+            assert!(p.0 * n.0 + p.1 * n.1 + p.2 * n.2 + p.3 * n.3 > 0.);
+        }
     }
 }
\ No newline at end of file
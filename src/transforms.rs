@@ -1,6 +1,6 @@
 use ndarray::prelude::*;
 
-use types::{Camera};
+use types::{Camera, Shape};
 
 pub fn dot_mv4(M: [[f32; 4]; 4], v: [f32; 4]) -> [f32; 4] {
     // Dot a len-4 matrix with a vec.
@@ -132,6 +132,97 @@ fn make_scaler4(scale: f32) -> [[f32; 4]; 4] {
     ]
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection4 {
+    // How points, relative to the camera, are taken from 4d to 3d. This happens
+    // before the 3d projection matrix, which then works as it would for a 3d scene.
+    // Drop u.
+    Orthographic,
+    // From an eye at u = -eye_dist, onto the u = 0 space. Points at higher u
+    // appear smaller, the same way points at higher z do.
+    Perspective { eye_dist: f32 },
+    // Project each point's direction from the camera stereographically: from the
+    // pole (0, 0, 0, 1) of the unit 3-sphere onto the u = 0 space. Then scale by
+    // the point's distance, so points along the same ray keep their depth order
+    // for the 3d projection and depth buffer. Directions at angle φ from the pole
+    // are spread out by cot(φ / 2); the u = 0 space is unchanged, and points in
+    // the direction of the pole go off to infinity. Scaling by distance cancels
+    // the sphere's radius, so there's no parameter.
+    Stereographic,
+}
+
+// project_4d from vert.glsl and shaders.ts, which both must contain this exactly,
+// so the Vulkan and WebGL frontends draw what Projection4::project computes. It
+// follows project line for line; change the three together. Points with no image
+// return false, and the shaders move them outside the clip volume.
+pub const PROJECT_4D_GLSL: &'static str = "
+bool project_4d(vec4 pt, int mode, float param, out vec3 projected) {
+    float scale = 1.;
+    if (mode == 1) {
+        float denom = param + pt[3];
+        if (denom <= 0.) { return false; }
+        scale = param / denom;
+    } else if (mode == 2) {
+        float denom = length(pt) - pt[3];
+        if (denom <= 0.) { return false; }
+        scale = length(pt) / denom;
+    }
+    projected = pt.xyz * scale;
+    return true;
+}
+";
+
+impl Projection4 {
+    pub fn project(&self, p: [f32; 4]) -> Option<[f32; 3]> {
+        // Returns None for points at or behind the eye, or in the direction of the
+        // pole; these have no finite image.
+        let scale = match *self {
+            Projection4::Orthographic => 1.,
+            Projection4::Perspective { eye_dist } => {
+                let denom = eye_dist + p[3];
+                if denom <= 0. { return None }
+                eye_dist / denom
+            },
+            Projection4::Stereographic => {
+                // The direction p / |p| projects to p.xyz / (|p| - p.u), which we
+                // scale by |p|.
+                let len = (0..4).map(|k| p[k].powi(2)).sum::<f32>().sqrt();
+                let denom = len - p[3];
+                if denom <= 0. { return None }
+                len / denom
+            },
+        };
+        Some([p[0] * scale, p[1] * scale, p[2] * scale])
+    }
+
+    pub fn to_uniforms(&self) -> (i32, f32) {
+        // The mode and parameter passed to the shaders, whose project_4d functions
+        // mirror project.
+        match *self {
+            Projection4::Orthographic => (0, 0.),
+            Projection4::Perspective { eye_dist } => (1, eye_dist),
+            Projection4::Stereographic => (2, 0.),
+        }
+    }
+
+    pub fn from_uniforms(mode: i32, param: f32) -> Self {
+        match mode {
+            1 => Projection4::Perspective { eye_dist: param },
+            2 => Projection4::Stereographic,
+            _ => Projection4::Orthographic,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        // Cycle through the modes, using default parameters.
+        match *self {
+            Projection4::Orthographic => Projection4::Perspective { eye_dist: 2. },
+            Projection4::Perspective { .. } => Projection4::Stereographic,
+            Projection4::Stereographic => Projection4::Orthographic,
+        }
+    }
+}
+
 pub fn make_proj_mat4(cam: &Camera) -> [[f32; 4]; 4] {
     // This variant returns a 4x4, non-homogenous matrix in the array format used
     // by Vulkan.
@@ -155,15 +246,12 @@ pub fn make_proj_mat4(cam: &Camera) -> [[f32; 4]; 4] {
     // to be used later.
 
     // Row 4 is used silently by Vulkan, to scale x and y to the frustum based on
-    // their z distance. If the last entry isn't 0, by u as well for 4d visual
-    // scaling cues. (Subjective). The values here are
+    // their z distance. 4d scaling cues are handled separately, by Projection4,
+    // before this matrix is applied. The values here are
     // negative, due to Vulkan's RHS coordinate system. z is divided by two here
     // and in other places due to Zulkan using a z dist of 0 to 1 in clipspace,
     // vice -1 to 1.
 
-    // todo: It appears that Vulkan will cause incorrect clipping  against
-    // todo the near plane if the u scaler is too high.
-
     // The terms in the third and fourth column turn out to be 0, unless the view is skewed, eg
     // more is shown to the right of center than left.  We don't do that, but leave
     // those terms in, for now.
@@ -175,7 +263,7 @@ pub fn make_proj_mat4(cam: &Camera) -> [[f32; 4]; 4] {
         // u_scale is, ultimately, not really used.
         // This row allows us to divide by z after taking the dot product,
         // as part of our scaling operation.
-        [0., 0., -0.5, -cam.fourd_proj_dist],
+        [0., 0., -0.5, 0.],
     ]
 }
 
@@ -198,8 +286,7 @@ pub fn make_proj_mat_gl(cam: &Camera) -> [[f32; 4]; 4] {
         // u_scale is, ultimately, not really used.
         // This row allows us to divide by z after taking the dot product,
         // as part of our scaling operation.
-          // * 2. at end is so we can use the same 4d proj factor in Vulkan and WebGl.
-        [0., 0., 1., cam.fourd_proj_dist * 2.]
+        [0., 0., 1., 0.]
     ]
 }
 
@@ -212,13 +299,449 @@ pub fn make_model_mat4(orientation: &Array1<f32>, scale: f32) -> [[f32; 4]; 4] {
 }
 
 pub fn make_view_mat4(θ: &Array1<f32>) -> [[f32; 4]; 4] {
-    // Non-homogenous, in the nested-array format used by Vulkan.
+    // Non-homogenous. Upload it with to_uniform_mat4.
 //    let negθ = array![-θ[0], -θ[1], -θ[2], -θ[3], -θ[4], -θ[5]];
     make_rotator4(&-θ)
 }
 
+pub fn shape_model_mat4(shape: &Shape) -> [[f32; 4]; 4] {
+    // The model matrix from a shape's rotor if it has one, or its orientation angles.
+    match shape.rotor {
+        Some(rotor) => dot_mm4(rotor.to_matrix(), make_scaler4(shape.scale)),
+        None => make_model_mat4(&shape.orientation, shape.scale),
+    }
+}
+
+pub fn cam_view_mat4(cam: &Camera) -> [[f32; 4]; 4] {
+    // The view matrix undoes the camera's rotation.
+    match cam.rotor {
+        Some(rotor) => rotor.reverse().to_matrix(),
+        None => make_view_mat4(&cam.θ),
+    }
+}
+
+pub fn transpose4(m: [[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut result = [[0.; 4]; 4];
+    for i in 0..4 {
+        for j in 0..4 {
+            result[i][j] = m[j][i];
+        }
+    }
+    result
+}
+
+pub fn to_uniform_mat4(m: [[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    // Vulkano and WebGL read a [[f32; 4]; 4] uniform column by column, so GLSL's
+    // `m * v` would apply the transpose of the nested array. Model, view and normal
+    // matrices go through this, so the shader applies them as dot_mv4 does. The
+    // projection matrix is already laid out in column order, and doesn't.
+    transpose4(m)
+}
+
+pub fn det4(m: [[f32; 4]; 4]) -> f32 {
+    // Expand along the first row, using 2x2 determinants of the bottom two rows.
+    let s = |a: usize, b: usize| m[2][a] * m[3][b] - m[2][b] * m[3][a];
+    let minor = |a: usize, b: usize, c: usize| m[1][a] * s(b, c) - m[1][b] * s(a, c) + m[1][c] * s(a, b);
+
+    m[0][0] * minor(1, 2, 3) - m[0][1] * minor(0, 2, 3) +
+        m[0][2] * minor(0, 1, 3) - m[0][3] * minor(0, 1, 2)
+}
+
+pub fn orthonormalize4(m: [[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    // Gram-Schmidt on the columns, to turn a rotation matrix that's drifted from
+    // floating-point error back into one. The first column keeps its direction.
+    let mut cols = transpose4(m);
+    for i in 0..4 {
+        for j in 0..i {
+            let d: f32 = (0..4).map(|k| cols[i][k] * cols[j][k]).sum();
+            for k in 0..4 {
+                cols[i][k] -= d * cols[j][k];
+            }
+        }
+        let len = (0..4).map(|k| cols[i][k].powi(2)).sum::<f32>().sqrt();
+        for k in 0..4 {
+            cols[i][k] /= len;
+        }
+    }
+    transpose4(cols)
+}
+
+pub fn make_normal_mat4(model: [[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    // Normals transform by the inverse transpose of the model matrix, which keeps
+    // them perpendicular to their faces under non-uniform scaling.
+    inverse4(model).map_or(I4(), transpose4)
+}
+
+pub fn inverse4(m: [[f32; 4]; 4]) -> Option<[[f32; 4]; 4]> {
+    // Invert a matrix with Gauss-Jordan elimination, or return None if it's singular.
+    let mut a = m;
+    let mut result = I4();
+    for col in 0..4 {
+        // Use the row with the largest entry in this column, for stability.
+        let pivot = (col..4).fold(col, |best, row| if a[row][col].abs() > a[best][col].abs() { row } else { best });
+        if a[pivot][col].abs() < 1e-12 {
+            return None
+        }
+        a.swap(col, pivot);
+        result.swap(col, pivot);
+
+        let scale = 1. / a[col][col];
+        for j in 0..4 {
+            a[col][j] *= scale;
+            result[col][j] *= scale;
+        }
+        for row in 0..4 {
+            if row == col { continue }
+            let factor = a[row][col];
+            for j in 0..4 {
+                a[row][j] -= factor * a[col][j];
+                result[row][j] -= factor * result[col][j];
+            }
+        }
+    }
+    Some(result)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform5 {
+    // A 4d affine transform: a 5x5 homogeneous matrix, with a last row of
+    // (0, 0, 0, 0, 1). We store it as its 4x4 linear part and its translation, which
+    // is how shaders receive it: GLSL has no 5x5 matrices.
+    pub linear: [[f32; 4]; 4],
+    pub translation: [f32; 4],
+}
+
+impl Transform5 {
+    pub fn new(linear: [[f32; 4]; 4], translation: [f32; 4]) -> Transform5 {
+        Transform5 { linear, translation }
+    }
+
+    pub fn identity() -> Transform5 {
+        Transform5::new(I4(), [0.; 4])
+    }
+
+    pub fn translation(offset: [f32; 4]) -> Transform5 {
+        Transform5::new(I4(), offset)
+    }
+
+    pub fn linear(m: [[f32; 4]; 4]) -> Transform5 {
+        Transform5::new(m, [0.; 4])
+    }
+
+    pub fn apply(&self, point: [f32; 4]) -> [f32; 4] {
+        let p = dot_mv4(self.linear, point);
+        [p[0] + self.translation[0], p[1] + self.translation[1],
+            p[2] + self.translation[2], p[3] + self.translation[3]]
+    }
+
+    pub fn apply_vector(&self, v: [f32; 4]) -> [f32; 4] {
+        // Directions aren't translated.
+        dot_mv4(self.linear, v)
+    }
+
+    pub fn then(&self, other: &Transform5) -> Transform5 {
+        // Apply self, then other.
+        let t = other.apply(self.translation);
+        Transform5::new(dot_mm4(other.linear, self.linear), t)
+    }
+
+    pub fn inverse(&self) -> Option<Transform5> {
+        // None if the linear part is singular, eg with a scale of 0.
+        inverse4(self.linear).map(|inv| {
+            let t = dot_mv4(inv, self.translation);
+            Transform5::new(inv, [-t[0], -t[1], -t[2], -t[3]])
+        })
+    }
+
+    pub fn to_mat5(&self) -> [[f32; 5]; 5] {
+        let mut result = [[0.; 5]; 5];
+        for i in 0..4 {
+            for j in 0..4 {
+                result[i][j] = self.linear[i][j];
+            }
+            result[i][4] = self.translation[i];
+        }
+        result[4][4] = 1.;
+        result
+    }
+}
+
+pub fn make_model_transform(shape: &Shape) -> Transform5 {
+    // Rotate and scale, then move to the shape's position.
+    let p = &shape.position;
+    Transform5::linear(shape_model_mat4(shape))
+        .then(&Transform5::translation([p[0], p[1], p[2], p[3]]))
+}
+
+pub fn make_view_transform(cam: &Camera) -> Transform5 {
+    // Move the camera to the origin, then undo its rotation.
+    let p = &cam.position;
+    Transform5::translation([-p[0], -p[1], -p[2], -p[3]])
+        .then(&Transform5::linear(cam_view_mat4(cam)))
+}
+
+pub fn make_screen_ray(cam: &Camera, screen_pt: (f32, f32)) -> ([f32; 4], [f32; 4]) {
+    // The ray from the camera through a point on the screen, as a world-space origin
+    // and unit direction. Eg for picking the shape under the cursor. The point is in
+    // Vulkan's normalized device coordinates: -1 to 1, with y down, as with window
+    // pixels. The ray stays in the camera's u = 0 space, which every 4d projection
+    // leaves in place.
+
+    // The rows of the projection as the shader applies it; see to_uniform_mat4.
+    // Only x, y and z are used, since the shader projects (x, y, z, 0).
+    let proj = transpose4(make_proj_mat4(cam));
+    let (sx, sy) = screen_pt;
+    // View-space points landing on the screen point satisfy clip.x = sx * clip.w
+    // and clip.y = sy * clip.w: two planes through the eye, which meet in the ray.
+    let a: Vec<f32> = (0..3).map(|k| proj[0][k] - sx * proj[3][k]).collect();
+    let b: Vec<f32> = (0..3).map(|k| proj[1][k] - sy * proj[3][k]).collect();
+    let mut dir = [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0], 0.];
+    // Point it the way the camera faces, where clip.w is positive.
+    if (0..3).map(|k| proj[3][k] * dir[k]).sum::<f32>() < 0. {
+        dir = [-dir[0], -dir[1], -dir[2], 0.];
+    }
+
+    let to_world = make_view_transform(cam).inverse().expect("The view transform is a rotation");
+    let origin = to_world.apply([0.; 4]);
+    let dir = to_world.apply_vector(dir);
+    let len = (0..4).map(|k| dir[k].powi(2)).sum::<f32>().sqrt();
+    (origin, [dir[0] / len, dir[1] / len, dir[2] / len, dir[3] / len])
+}
+
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
+    use std::f32::consts::PI;
+    use rotor::{Bivector4, Rotor4};
+    use types::Mesh;
+
+    fn assert_close(a: [f32; 4], b: [f32; 4]) {
+        for i in 0..4 {
+            assert!((a[i] - b[i]).abs() < 1e-5, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_transform5() {
+        let rotate = Transform5::linear(make_model_mat4(&array![0.3, -1.2, 2.5, 0.8, -0.4, 1.9], 2.));
+        let shift = Transform5::translation([1., -2., 3., 0.5]);
+        let p = [0.5, 1., -1., 2.];
+
+        let both = rotate.then(&shift);
+        assert_close(both.apply(p), shift.apply(rotate.apply(p)));
+        assert_close(both.apply_vector(p), rotate.apply_vector(p));
+
+        let inverse = both.inverse().unwrap();
+        assert_close(inverse.apply(both.apply(p)), p);
+        assert_close(both.then(&inverse).apply(p), p);
+        assert_eq!(Transform5::linear(make_scaler4(0.)).inverse(), None);
+
+        // The 5x5 matrix acts on homogeneous points (x, y, z, w, 1).
+        let m = both.to_mat5();
+        let p5 = [p[0], p[1], p[2], p[3], 1.];
+        let q: Vec<f32> = (0..5).map(|i| (0..5).map(|j| m[i][j] * p5[j]).sum()).collect();
+        assert_close([q[0], q[1], q[2], q[3]], both.apply(p));
+        assert_eq!(q[4], 1.);
+    }
+
+    fn shader_apply(uploaded: [[f32; 4]; 4], translation: [f32; 4], v: [f32; 4]) -> [f32; 4] {
+        // What `mat * v + translation` gives in GLSL, for a mat4 uploaded from a
+        // nested array: the array's rows are the matrix's columns.
+        let mut result = translation;
+        for i in 0..4 {
+            for j in 0..4 {
+                result[i] += uploaded[j][i] * v[j];
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_transforms_match_shader() {
+        let mut shape = Shape::new(Mesh::new(HashMap::new(), vec![], vec![]), array![1., -2., 0.5, 3.],
+            array![0.3, -1.2, 2.5, 0.8, -0.4, 1.9], Array::zeros(6), 1.);
+        shape.scale = 1.5;
+        let cam = Camera {
+            position: array![1., 2., 3., 4.],
+            θ: array![0.4, -0.3, 0.7, 0.2, 0.5, -0.6],
+            fov: PI / 2., aspect: 2., aspect_4: 1., near: 0.1, far: 100.,
+            projection: Projection4::Orthographic, rotor: None,
+        };
+        let model = make_model_transform(&shape);
+        let view = make_view_transform(&cam);
+        let p = [0.5, 1., -1., 2.];
+
+        // The uniforms render_vulcano uploads give the same points as the transforms.
+        let world = shader_apply(to_uniform_mat4(model.linear), model.translation, p);
+        assert_close(world, model.apply(p));
+        let viewed = shader_apply(to_uniform_mat4(view.linear), view.translation, world);
+        assert_close(viewed, view.apply(world));
+
+        // Which is how dot_mv4 applies the nested arrays.
+        let s = &shape.position;
+        let modeled = dot_mv4(shape_model_mat4(&shape), p);
+        assert_close(world, [modeled[0] + s[0], modeled[1] + s[1], modeled[2] + s[2], modeled[3] + s[3]]);
+
+        // Normals go through the inverse transpose of the drawn model matrix.
+        let normal = [0., 1., 0., 0.];
+        let tangent = [1., 0., 0., 0.];
+        let n = shader_apply(to_uniform_mat4(make_normal_mat4(model.linear)), [0.; 4], normal);
+        let t = model.apply_vector(tangent);
+        assert!((0..4).map(|k| n[k] * t[k]).sum::<f32>().abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_turn_in_world_planes() {
+        // Turns of a shape with a rotor are in the world's planes, so a turn in xu
+        // then one in yz is drawn as those two rotations, in that order.
+        let mut shape = Shape::new(Mesh::new(HashMap::new(), vec![], vec![]), array![1., -2., 0.5, 3.],
+            Array::zeros(6), Array::zeros(6), 1.);
+        shape.rotor = Some(Rotor4::identity());
+        shape.turn(3, 0.6);
+        shape.turn(1, -1.1);
+
+        let xu = Rotor4::from_angles(&array![0., 0., 0., 0.6, 0., 0.]);
+        let yz = Rotor4::from_angles(&array![0., -1.1, 0., 0., 0., 0.]);
+        let p = [0.5, 1., -1., 2.];
+        let turned = yz.rotate(xu.rotate(p));
+        let expected = [turned[0] + 1., turned[1] - 2., turned[2] + 0.5, turned[3] + 3.];
+        assert_close(make_model_transform(&shape).apply(p), expected);
+
+        // A turn in xz alone moves x towards z, as make_rotator4 does.
+        let mut shape = Shape { position: Array::zeros(4), ..shape };
+        shape.rotor = Some(Rotor4::identity());
+        shape.turn(2, 0.6);
+        assert_close(make_model_transform(&shape).apply([1., 0., 0., 0.]), [0.6f32.cos(), 0., 0.6f32.sin(), 0.]);
+
+        // Spinning integrates in world planes too.
+        let ω = Bivector4::new(0., 0., 0.9, 0., 0., 0.);
+        let before = make_model_transform(&shape).apply(p);
+        shape.rotor = Some(shape.rotor.unwrap().integrate(&ω, 0.5));
+        assert_close(make_model_transform(&shape).apply(p), ω.scale(0.5).exp().rotate(before));
+    }
+
+    #[test]
+    fn test_project_4d_shaders() {
+        // Compare ignoring indentation, which differs in shaders.ts's template string.
+        let squash = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let expected = squash(PROJECT_4D_GLSL);
+        assert!(squash(include_str!("vert.glsl")).contains(&expected));
+        assert!(squash(include_str!("../frontend/src/shaders.ts")).contains(&expected));
+    }
+
+    #[test]
+    fn test_inverse4() {
+        let m = [[2., 0.5, -1., 0.], [0., 1., 3., 1.], [1., -2., 0., 0.5], [0.5, 0., 1., -1.]];
+        let inv = inverse4(m).unwrap();
+        let identity = dot_mm4(m, inv);
+        for i in 0..4 {
+            assert_close(identity[i], I4()[i]);
+        }
+        assert!((det4(m) * det4(inv) - 1.).abs() < 1e-5);
+        assert_eq!(transpose4(transpose4(m)), m);
+
+        // A repeated row.
+        let singular = [m[0], m[1], m[0], m[3]];
+        assert!(det4(singular).abs() < 1e-6);
+        assert_eq!(inverse4(singular), None);
+    }
+
+    #[test]
+    fn test_orthonormalize4() {
+        let rotation = make_rotator4(&array![0.3, -1.2, 2.5, 0.8, -0.4, 1.9]);
+        // Already orthonormal, so unchanged.
+        let fixed = orthonormalize4(rotation);
+        for i in 0..4 {
+            assert_close(fixed[i], rotation[i]);
+        }
+
+        let mut drifted = rotation;
+        drifted[1][2] += 0.01;
+        drifted[3][0] -= 0.02;
+        let fixed = orthonormalize4(drifted);
+        let product = dot_mm4(transpose4(fixed), fixed);
+        for i in 0..4 {
+            assert_close(product[i], I4()[i]);
+        }
+        assert!((det4(fixed) - 1.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_screen_ray() {
+        let cam = Camera {
+            position: array![1., 2., 3., 4.],
+            θ: array![0.4, -0.3, 0.7, 0.2, 0.5, -0.6],
+            fov: PI / 2., aspect: 2., aspect_4: 1., near: 0.1, far: 100.,
+            projection: Projection4::Perspective { eye_dist: 2. }, rotor: None,
+        };
+        let view = make_view_transform(&cam);
+        let proj = make_proj_mat4(&cam);
+        let screen_pt = (0.5, -0.25);
+        let (origin, dir) = make_screen_ray(&cam, screen_pt);
+        assert_close(origin, [1., 2., 3., 4.]);
+
+        // Points along the ray land on the screen point, going through the uniforms
+        // as render_vulcano uploads them and the shader applies them.
+        for &dist in &[1., 3., 20.] {
+            let world = [origin[0] + dist * dir[0], origin[1] + dist * dir[1],
+                         origin[2] + dist * dir[2], origin[3] + dist * dir[3]];
+            let viewed = shader_apply(to_uniform_mat4(view.linear), view.translation, world);
+            let q = cam.projection.project(viewed).unwrap();
+            let clip = shader_apply(proj, [0.; 4], [q[0], q[1], q[2], 0.]);
+            assert!(clip[3] > 0.);
+            assert!(clip[2] >= 0. && clip[2] <= clip[3]);
+            assert!((clip[0] / clip[3] - screen_pt.0).abs() < 1e-4);
+            assert!((clip[1] / clip[3] - screen_pt.1).abs() < 1e-4);
+        }
+
+        // Screen y is down, so a ray through the top of the screen points up in
+        // the camera's view.
+        let (_, up) = make_screen_ray(&cam, (0., -0.5));
+        assert!(view.apply_vector(up)[1] > 0.);
+    }
+
+    #[test]
+    fn test_projection4() {
+        let p = [1., -2., 3., 0.5];
+        assert_eq!(Projection4::Orthographic.project(p), Some([1., -2., 3.]));
+
+        // Points in the u = 0 space are unchanged by every mode.
+        let flat = [1., -2., 3., 0.];
+        let persp = Projection4::Perspective { eye_dist: 2. };
+        let stereo = Projection4::Stereographic;
+        assert_eq!(persp.project(flat), Some([1., -2., 3.]));
+        assert_eq!(stereo.project(flat), Some([1., -2., 3.]));
+
+        // Perspective shrinks points further away in u, and can't see behind the eye.
+        assert_eq!(persp.project([1., -2., 3., 2.]), Some([0.5, -1., 1.5]));
+        assert_eq!(persp.project([1., -2., 3., -1.]), Some([2., -4., 6.]));
+        assert_eq!(persp.project([1., -2., 3., -2.]), None);
+
+        // A direction at angle φ from the pole lands cot(φ / 2) from the origin, per
+        // unit of distance, and the opposite pole at the origin.
+        let φ = PI / 3.;
+        let dir = [φ.sin(), 0., 0., φ.cos()];
+        let projected = stereo.project(dir).unwrap();
+        assert!((projected[0] - 1. / (φ / 2.).tan()).abs() < 1e-5);
+        assert_eq!(stereo.project([0., 0., 0., -2.]), Some([0., 0., 0.]));
+        assert_eq!(stereo.project([0., 0., 0., 2.]), None);
+        // And there's nothing to project at the camera itself.
+        assert_eq!(stereo.project([0.; 4]), None);
+
+        // Points further along the same ray stay further away, so it isn't
+        // perspective mirrored in u, and depth survives the 3d projection.
+        let near = stereo.project([0.2, -0.3, 1., 0.6]).unwrap();
+        let far = stereo.project([0.6, -0.9, 3., 1.8]).unwrap();
+        assert!(far[2] > near[2]);
+        for k in 0..3 {
+            assert!((far[k] - 3. * near[k]).abs() < 1e-5);
+        }
+
+        for proj in &[Projection4::Orthographic, persp, stereo] {
+            let (mode, param) = proj.to_uniforms();
+            assert_eq!(Projection4::from_uniforms(mode, param), *proj);
+        }
+    }
 }
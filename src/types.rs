@@ -1,7 +1,11 @@
 use std::collections::HashMap;
+use std::{error, fmt};
 
 use ndarray::prelude::*;
 
+use rotor::Rotor4;
+use transforms::Projection4;
+
 #[derive(Debug)]
 pub struct Pt2D {
     pub x: f32,
@@ -23,8 +27,41 @@ impl Vertex {
                     self.position.2 - other.position.2, self.position.3 - other.position.3,)
     }
 
+    pub fn add(&self, other: &Vertex) -> Vertex {
+        Vertex::new(self.position.0 + other.position.0, self.position.1 + other.position.1,
+                    self.position.2 + other.position.2, self.position.3 + other.position.3)
+    }
+
+    pub fn dot(&self, other: &Vertex) -> f32 {
+        self.position.0 * other.position.0 + self.position.1 * other.position.1 +
+            self.position.2 * other.position.2 + self.position.3 * other.position.3
+    }
+
+    pub fn scale(&self, s: f32) -> Vertex {
+        Vertex::new(self.position.0 * s, self.position.1 * s, self.position.2 * s, self.position.3 * s)
+    }
+
+    pub fn cross4(&self, b: &Vertex, c: &Vertex) -> Normal {
+        // The generalized cross product: a vector perpendicular to self, b and c,
+        // eg the normal of a 3d cell with these as edges. Its length is the volume
+        // of the parallelepiped they span. cross4(a, b, (0, 0, 0, 1)) is the 3d
+        // cross product of a and b, with u = 0.
+        let (a, b, c) = (self.position, b.position, c.position);
+        let a = [a.0, a.1, a.2, a.3];
+        let b = [b.0, b.1, b.2, b.3];
+        let c = [c.0, c.1, c.2, c.3];
+
+        // Each component is a signed 3x3 minor of the matrix with rows a, b, c.
+        let minor = |i: usize, j: usize, k: usize| {
+            a[i] * (b[j] * c[k] - b[k] * c[j]) -
+                a[j] * (b[i] * c[k] - b[k] * c[i]) +
+                a[k] * (b[i] * c[j] - b[j] * c[i])
+        };
+        Normal::new(minor(1, 2, 3), -minor(0, 2, 3), minor(0, 1, 3), -minor(0, 1, 2))
+    }
+
     pub fn cross(&self, other: &Vertex) -> Normal {
-        // Ignores the u component; cross product isn't defined for len-4 vectors.
+        // Ignores the u component; for 4d, use cross4.
         Normal::new(
             self.position.1 * other.position.2 - self.position.2 * other.position.1,
             self.position.2 * other.position.0 - self.position.0 * other.position.2,
@@ -40,7 +77,6 @@ pub struct VertAndExtras {
     // We do the impl_vertex in render_vulkano, so we don't need to import vulkano
     // in the wasm target.
     pub position: (f32, f32, f32, f32),
-    pub shape_posit: (f32, f32, f32, f32),
     pub normal: (f32, f32, f32, f32),
     pub specular_intensity: f32,
 }
@@ -51,30 +87,365 @@ pub struct Normal {
 }
 
 impl Normal {
-    // Only really uses the 3d part of the shape, for now.
     pub fn new(x: f32, y: f32, z: f32, u: f32) -> Normal {
         Normal{ normal: (x, y, z, u) }
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum MeshError {
+    // Problems with a mesh's structure, eg from user input.
+    MissingVertex { face: usize, vertex: u32 },  // A face uses an id not in vertices.
+    TooFewVertices { face: usize, count: usize },
+    NormalCount { faces: usize, normals: usize },  // Each face needs one normal.
+    NonPlanarFace { face: usize },
+    DuplicateVertices(u32, u32),  // Two ids at the same position.
+    // An edge bordering more than two faces. Only checked for 3d meshes (ones with
+    // a constant w); edges of 4d shapes are normally shared by three or more faces.
+    NonManifoldEdge { edge: (u32, u32), faces: usize },
+    NonFiniteVertex(u32),  // A vertex with a NaN or infinite coordinate.
+    MissingFace { cell: usize, face: u32 },  // A cell uses a face index that's out of range.
+    OpenCell { cell: usize, edge: (u32, u32) },  // A cell edge that doesn't border exactly two of its faces.
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MeshError::MissingVertex { face, vertex } =>
+                write!(f, "Face {} uses vertex {}, which doesn't exist.", face, vertex),
+            MeshError::TooFewVertices { face, count } =>
+                write!(f, "Face {} has {} vertices; faces must have 3 or more.", face, count),
+            MeshError::NormalCount { faces, normals } =>
+                write!(f, "There are {} normals for {} faces.", normals, faces),
+            MeshError::NonPlanarFace { face } => write!(f, "Face {} isn't planar.", face),
+            MeshError::DuplicateVertices(a, b) =>
+                write!(f, "Vertices {} and {} are at the same position.", a, b),
+            MeshError::NonManifoldEdge { edge, faces } =>
+                write!(f, "The edge from {} to {} borders {} faces.", edge.0, edge.1, faces),
+            MeshError::NonFiniteVertex(id) => write!(f, "Vertex {} has a non-finite coordinate.", id),
+            MeshError::MissingFace { cell, face } =>
+                write!(f, "Cell {} uses face {}, which doesn't exist.", cell, face),
+            MeshError::OpenCell { cell, edge } =>
+                write!(f, "Cell {} isn't closed at the edge from {} to {}.", cell, edge.0, edge.1),
+        }
+    }
+}
+
+impl error::Error for MeshError {}
+
 #[derive(Clone, Debug)]
 pub struct Mesh {
-    pub vertices: HashMap<u32, Vertex>,
+    pub vertices: Vec<Vertex>,  // Faces refer to these by index.
     pub faces_vert: Vec<Array1<u32>>,  // Indicies of vertexes.
-    pub normals: Vec<Normal>,  // Normals only use the 3d component; not defined for 4d, yet. ?
+    pub normals: Vec<Normal>,  // One per face, in 4d.
     pub tris: Array1<u32>,
+    // Indices of the faces bounding each 3d cell, for shapes that have them. Empty
+    // if the mesh doesn't know its cells.
+    pub cells: Vec<Array1<u32>>,
+}
+
+fn plane_basis(offsets: &[Vertex]) -> (Vertex, Vertex) {
+    // Find an orthonormal basis for the plane of a face, from its vertices' offsets
+    // from the first one. The first axis runs towards the first point that isn't
+    // on top of the first one, and the second towards whichever point is furthest
+    // from it. Axes a degenerate face doesn't have are left as 0, instead of NaN.
+    let unit = |v: Vertex| {
+        let len = v.dot(&v).sqrt();
+        if len > 0. { v.scale(1. / len) } else { v }
+    };
+    let u = unit(offsets.iter().find(|p| p.dot(p) > 0.).cloned().unwrap_or(offsets[0]));
+
+    let mut v = Vertex::new(0., 0., 0., 0.);
+    for offset in offsets {
+        let residual = offset.subtract(&u.scale(offset.dot(&u)));
+        if residual.dot(&residual) > v.dot(&v) {
+            v = residual;
+        }
+    }
+    (u, unit(v))
+}
+
+fn project_to_plane(points: &[Vertex]) -> Vec<(f32, f32)> {
+    // Find 2d coordinates for points on a plane in 4d.
+    let offsets: Vec<Vertex> = points.iter().map(|p| p.subtract(&points[0])).collect();
+    let (u, v) = plane_basis(&offsets);
+    offsets.iter().map(|p| (p.dot(&u), p.dot(&v))).collect()
+}
+
+fn split_at_repeats(face: Vec<u32>) -> Vec<Vec<u32>> {
+    // Split a face's loop of vertex ids wherever it passes through the same vertex
+    // twice, eg [a, b, c, a, d, e] into [a, b, c] and [a, d, e]. Pieces with fewer
+    // than three vertices, like the [a, a] from a repeated neighbor, have no area
+    // and are dropped.
+    let mut result = Vec::new();
+    let mut current: Vec<u32> = Vec::new();
+    for id in face {
+        match current.iter().position(|v| *v == id) {
+            Some(i) => {
+                // The loop since id was last seen; current goes back to that point.
+                let mut piece = current.split_off(i + 1);
+                piece.insert(0, id);
+                if piece.len() >= 3 {
+                    result.push(piece);
+                }
+            },
+            None => current.push(id),
+        }
+    }
+    if current.len() >= 3 {
+        result.push(current);
+    }
+    result
+}
+
+fn clip_ears(points: &[(f32, f32)]) -> Vec<[usize; 3]> {
+    // Triangulate a simple polygon by repeatedly cutting off an 'ear': a corner
+    // whose triangle is convex, and contains no other corners.
+    let cross = |a: (f32, f32), b: (f32, f32), c: (f32, f32)|
+        (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+
+    // Orient every corner test the same way as the polygon.
+    let n = points.len();
+    let area = (0..n).fold(0., |acc, i| {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        acc + a.0 * b.1 - b.0 * a.1
+    });
+    let sign = if area < 0. { -1. } else { 1. };
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut result = Vec::new();
+
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        // Start at the second corner, so convex polygons fan out from the first.
+        // If rounding leaves no ears, clip one anyway rather than looping forever.
+        let ear = {
+            let is_ear = |i: usize| {
+                let (a, b, c) = (remaining[(i + m - 1) % m], remaining[i], remaining[(i + 1) % m]);
+                let (pa, pb, pc) = (points[a], points[b], points[c]);
+                if sign * cross(pa, pb, pc) <= 0. {
+                    return false
+                }
+                // Corners on the edge of the ear count as inside it.
+                !remaining.iter()
+                    .filter(|j| **j != a && **j != b && **j != c)
+                    .any(|j| {
+                        let p = points[*j];
+                        sign * cross(pa, pb, p) >= 0. && sign * cross(pb, pc, p) >= 0. &&
+                            sign * cross(pc, pa, p) >= 0.
+                    })
+            };
+
+            (1..m + 1).map(|i| i % m).find(|i| is_ear(*i)).unwrap_or(1)
+        };
+
+        result.push([remaining[(ear + m - 1) % m], remaining[ear], remaining[(ear + 1) % m]]);
+        remaining.remove(ear);
+    }
+
+    result.push([remaining[0], remaining[1], remaining[2]]);
+    result
+}
+
+pub trait IntoVertices {
+    // Collections a mesh's vertices can be given as. Returns the vertices as a
+    // list, and, if the collection's ids aren't already indices into it, the index
+    // of each id.
+    fn into_vertices(self) -> (Vec<Vertex>, Option<HashMap<u32, u32>>);
+}
+
+impl IntoVertices for Vec<Vertex> {
+    fn into_vertices(self) -> (Vec<Vertex>, Option<HashMap<u32, u32>>) {
+        (self, None)
+    }
+}
+
+impl IntoVertices for HashMap<u32, Vertex> {
+    fn into_vertices(self) -> (Vec<Vertex>, Option<HashMap<u32, u32>>) {
+        // Keep the ids' order. Most generators number vertices from 0 without gaps,
+        // and then don't need remapping.
+        let mut ids: Vec<u32> = self.keys().cloned().collect();
+        ids.sort();
+        let vertices = ids.iter().map(|id| self[id]).collect();
+
+        if ids.iter().enumerate().all(|(i, id)| i as u32 == *id) {
+            (vertices, None)
+        } else {
+            (vertices, Some(ids.into_iter().enumerate().map(|(i, id)| (id, i as u32)).collect()))
+        }
+    }
 }
 
 impl Mesh {
-    pub fn new(vertices: HashMap<u32, Vertex>,
+    pub fn new<V: IntoVertices>(vertices: V,
                faces_vert: Vec<Array1<u32>>, normals: Vec<Normal>) -> Mesh {
+        // For meshes built in code. Panics if a face uses a vertex that doesn't
+        // exist; meshes from user input should go through try_new instead.
+        let mut result = match Mesh::from_parts(vertices, faces_vert, normals) {
+            Ok(mesh) => mesh,
+            Err(e) => panic!("{}", e),
+        };
+        result.make_tris();
+        result
+    }
 
-        let mut result = Mesh {vertices, faces_vert, normals, tris: array![]};
+    pub fn try_new<V: IntoVertices>(vertices: V,
+                   faces_vert: Vec<Array1<u32>>, normals: Vec<Normal>) -> Result<Mesh, MeshError> {
+        // As new, but returns an error instead of panicking on a broken mesh.
+        let mut result = Mesh::from_parts(vertices, faces_vert, normals)?;
+        result.validate()?;
         result.make_tris();
+        Ok(result)
+    }
+
+    fn from_parts<V: IntoVertices>(vertices: V, mut faces_vert: Vec<Array1<u32>>,
+                                   normals: Vec<Normal>) -> Result<Mesh, MeshError> {
+        // Store the vertices as a list, pointing faces at their indices.
+        let (vertices, indices) = vertices.into_vertices();
+        for (i, face) in faces_vert.iter_mut().enumerate() {
+            for id in face.iter_mut() {
+                let index = match indices {
+                    Some(ref indices) => indices.get(id).cloned(),
+                    None => Some(*id),
+                };
+                match index {
+                    Some(index) if (index as usize) < vertices.len() => *id = index,
+                    _ => return Err(MeshError::MissingVertex { face: i, vertex: *id }),
+                }
+            }
+        }
+        Ok(Mesh { vertices, faces_vert, normals, tris: array![], cells: Vec::new() })
+    }
+
+    fn check_indices(&self) -> Result<(), MeshError> {
+        // Check that every face's vertices and normal exist; anything indexing
+        // into the mesh relies on this.
+        for (i, face) in self.faces_vert.iter().enumerate() {
+            if let Some(id) = face.iter().find(|id| **id as usize >= self.vertices.len()) {
+                return Err(MeshError::MissingVertex { face: i, vertex: *id })
+            }
+        }
+
+        if self.normals.len() != self.faces_vert.len() {
+            return Err(MeshError::NormalCount { faces: self.faces_vert.len(), normals: self.normals.len() })
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), MeshError> {
+        // Check the mesh is well-formed, returning the first problem found.
+        self.check_indices()?;
+        for (i, face) in self.faces_vert.iter().enumerate() {
+            if face.len() < 3 {
+                return Err(MeshError::TooFewVertices { face: i, count: face.len() })
+            }
+        }
+
+        // The geometric checks below assume real coordinates.
+        if let Some(id) = self.vertices.iter().position(|v| {
+            let p = v.position;
+            !(p.0.is_finite() && p.1.is_finite() && p.2.is_finite() && p.3.is_finite())
+        }) {
+            return Err(MeshError::NonFiniteVertex(id as u32))
+        }
+
+        for (i, face) in self.faces_vert.iter().enumerate() {
+            if face.len() == 3 { continue }
+            let offsets: Vec<Vertex> = face.iter()
+                .map(|id| self.vertices[*id as usize].subtract(&self.vertices[face[0] as usize]))
+                .collect();
+            let size = offsets.iter().fold(0., |acc: f32, p| acc.max(p.dot(p).sqrt()));
+            let (u, v) = plane_basis(&offsets);
+            let flat = offsets.iter().all(|p| {
+                let residual = p.subtract(&u.scale(p.dot(&u))).subtract(&v.scale(p.dot(&v)));
+                residual.dot(&residual).sqrt() <= 1e-4 * size
+            });
+            if !flat {
+                return Err(MeshError::NonPlanarFace { face: i })
+            }
+        }
+
+        // Look for duplicates among vertices sorted by x, only comparing ones close in x.
+        let extent = self.vertices.iter().fold(1., |acc: f32, v| {
+            let p = v.position;
+            acc.max(p.0.abs()).max(p.1.abs()).max(p.2.abs()).max(p.3.abs())
+        });
+        let eps = extent * 1e-5;
+        let mut sorted: Vec<(u32, &Vertex)> = self.vertices.iter().enumerate()
+            .map(|(id, v)| (id as u32, v)).collect();
+        // Coordinates are finite, so partial_cmp always succeeds.
+        sorted.sort_by(|a, b| (a.1.position.0, a.0).partial_cmp(&(b.1.position.0, b.0)).unwrap());
+        for (i, (id, vert)) in sorted.iter().enumerate() {
+            for (other_id, other) in &sorted[i + 1..] {
+                if other.position.0 - vert.position.0 > eps { break }
+                let diff = other.subtract(vert);
+                if diff.dot(&diff).sqrt() <= eps {
+                    let (a, b) = (*id, *other_id);
+                    return Err(MeshError::DuplicateVertices(a.min(b), a.max(b)))
+                }
+            }
+        }
+
+        let w = self.vertices.first().map_or(0., |v| v.position.3);
+        if self.vertices.iter().all(|v| (v.position.3 - w).abs() <= eps) {
+            let mut edge_counts = HashMap::new();
+            for face in &self.faces_vert {
+                for i in 0..face.len() {
+                    let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                    *edge_counts.entry(if a < b { (a, b) } else { (b, a) }).or_insert(0) += 1;
+                }
+            }
+            let mut edges: Vec<((u32, u32), usize)> = edge_counts.into_iter().collect();
+            edges.sort();
+            if let Some((edge, faces)) = edges.into_iter().find(|(_, count)| *count > 2) {
+                return Err(MeshError::NonManifoldEdge { edge, faces })
+            }
+        }
+
+        // Each cell is a closed surface: every edge borders two of its faces.
+        for (i, cell) in self.cells.iter().enumerate() {
+            if let Some(face) = cell.iter().find(|f| **f as usize >= self.faces_vert.len()) {
+                return Err(MeshError::MissingFace { cell: i, face: *face })
+            }
+
+            let mut edge_counts = HashMap::new();
+            for face_id in cell {
+                let face = &self.faces_vert[*face_id as usize];
+                for j in 0..face.len() {
+                    let (a, b) = (face[j], face[(j + 1) % face.len()]);
+                    *edge_counts.entry(if a < b { (a, b) } else { (b, a) }).or_insert(0) += 1;
+                }
+            }
+            let mut edges: Vec<((u32, u32), usize)> = edge_counts.into_iter().collect();
+            edges.sort();
+            if let Some((edge, _)) = edges.into_iter().find(|(_, count)| *count != 2) {
+                return Err(MeshError::OpenCell { cell: i, edge })
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn cells_of_face(&self, face: usize) -> Vec<usize> {
+        // Find the cells a face bounds; two, for a closed 4d shape.
+        self.cells.iter().enumerate()
+            .filter(|(_, cell)| cell.iter().any(|f| *f as usize == face))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn cell_neighbors(&self, cell: usize) -> Vec<usize> {
+        // Find the cells that share a face with this one.
+        let mut result: Vec<usize> = self.cells[cell].iter()
+            .flat_map(|f| self.cells_of_face(*f as usize))
+            .filter(|c| *c != cell)
+            .collect();
+        result.sort();
+        result.dedup();
         result
     }
 
-        pub fn make_tris(&mut self) {
+    pub fn make_tris(&mut self) {
         // Divide faces into triangles of indices. These indices aren't of node
         // ids; rather of cumulative node ids; eg how they'll appear in an index buffer.
         // Result is a 1d array.
@@ -85,6 +456,8 @@ impl Mesh {
 
         for face in &self.faces_vert {
             match face.len() {
+                // Faces this short have no area; validate reports them.
+                0 | 1 | 2 => (),
                 3 => {
                 // Only one triangle.
                 result.push(current_i as u32);
@@ -92,17 +465,31 @@ impl Mesh {
                 result.push(current_i as u32 + 2);
             },
                 4 => {
-                // First triangle
-                result.push(current_i as u32);
-                result.push(current_i as u32 + 1);
-                result.push(current_i as u32 + 2);
-                // Second triangle
-                result.push(current_i as u32);
-                result.push(current_i as u32 + 2);
-                result.push(current_i as u32 + 3);
+                // Split along the 0-2 diagonal, unless the face is concave at vertex
+                // 1 or 3. That diagonal's inside the face when the triangles either
+                // side of it wind the same way: (a ∧ e) · (e ∧ b) > 0.
+                let p = |i: usize| self.vertices[face[i] as usize];
+                let e = p(2).subtract(&p(0));
+                let (a, b) = (p(1).subtract(&p(0)), p(3).subtract(&p(0)));
+                let order = if a.dot(&e) * e.dot(&b) - a.dot(&b) * e.dot(&e) > 0. {
+                    [0, 1, 2, 0, 2, 3]
+                } else {
+                    [1, 2, 3, 1, 3, 0]
+                };
+                for i in order.iter() {
+                    result.push((current_i + i) as u32);
+                }
             },
-                2 => panic!("Faces must have length 3 or more."),
-                _ => panic!("Error: Haven't implemented faces with vertex counds higher than four.")
+                _ => {
+                // Faces can be concave, so clip ears. For convex faces, this
+                // gives the same triangles as fanning out from the first vertex.
+                let points: Vec<Vertex> = face.iter().map(|id| self.vertices[*id as usize]).collect();
+                for tri in clip_ears(&project_to_plane(&points)) {
+                    for i in tri.iter() {
+                        result.push((current_i + i) as u32);
+                    }
+                }
+            }
 
             }
             current_i += face.len();
@@ -110,16 +497,177 @@ impl Mesh {
         self.tris = Array::from_vec(result)
     }
 
+    pub fn edges(&self) -> Vec<(u32, u32)> {
+        // Find the unique edges of the faces, as sorted pairs of vertex ids with the
+        // lower id first.
+        let mut result = Vec::new();
+        for face in &self.faces_vert {
+            for i in 0..face.len() {
+                let (a, b) = (face[i], face[(i + 1) % face.len()]);
+                result.push(if a < b { (a, b) } else { (b, a) });
+            }
+        }
+        result.sort();
+        result.dedup();
+        result
+    }
+
     pub fn num_face_verts(&self) -> u32 {
         // Find the number of vertices used in drawing faces.  Ie for a box,
         // it's 6 faces x 4 vertices/face.
         self.faces_vert.iter().fold(0, |acc, face| acc + face.len() as u32)
     }
     
+    pub fn weld(&mut self, epsilon: f32) {
+        // Merge vertices closer than epsilon, eg along the seams of meshes joined
+        // with combine_meshes. Faces that collapse to fewer than three vertices are
+        // removed, along with their normals and places in cells. Faces pinched
+        // together at a vertex are split there. Vertices with non-finite
+        // coordinates are left alone.
+        let mut sorted: Vec<usize> = (0..self.vertices.len())
+            .filter(|i| {
+                let p = self.vertices[*i].position;
+                p.0.is_finite() && p.1.is_finite() && p.2.is_finite() && p.3.is_finite()
+            }).collect();
+        sorted.sort_by(|a, b| self.vertices[*a].position.0.partial_cmp(&self.vertices[*b].position.0).unwrap());
+
+        // Sweep along x, comparing each vertex with the kept ones close to it in x.
+        let mut kept: Vec<usize> = Vec::new();
+        let mut merged_into: Vec<u32> = (0..self.vertices.len() as u32).collect();
+        for i in sorted {
+            let p = self.vertices[i];
+            let near = kept.iter().rev()
+                .take_while(|k| p.position.0 - self.vertices[**k].position.0 <= epsilon)
+                .find(|k| {
+                    let diff = p.subtract(&self.vertices[**k]);
+                    diff.dot(&diff).sqrt() <= epsilon
+                }).cloned();
+            match near {
+                Some(k) => merged_into[i] = k as u32,
+                None => kept.push(i),
+            }
+        }
+
+        let mut faces_vert = Vec::new();
+        let mut normals = Vec::new();
+        // The faces each old face became.
+        let mut new_face_ids = vec![Vec::new(); self.faces_vert.len()];
+        for (i, (face, normal)) in self.faces_vert.iter().zip(&self.normals).enumerate() {
+            let welded = face.iter().map(|id| merged_into[*id as usize]).collect();
+            for piece in split_at_repeats(welded) {
+                new_face_ids[i].push(faces_vert.len() as u32);
+                faces_vert.push(Array::from_vec(piece));
+                normals.push(*normal);
+            }
+        }
+
+        self.cells = self.cells.iter()
+            .map(|cell| cell.iter().flat_map(|f| new_face_ids[*f as usize].clone()).collect())
+            .collect();
+        self.faces_vert = faces_vert;
+        self.normals = normals;
+        self.remove_unused_vertices();
+    }
+
+    pub fn remove_unused_vertices(&mut self) {
+        // Drop vertices no face uses, renumbering the rest in order.
+        let mut new_ids = vec![None; self.vertices.len()];
+        for face in &self.faces_vert {
+            for id in face {
+                new_ids[*id as usize] = Some(0);
+            }
+        }
+
+        let mut vertices = Vec::new();
+        for (i, new_id) in new_ids.iter_mut().enumerate() {
+            if new_id.is_some() {
+                *new_id = Some(vertices.len() as u32);
+                vertices.push(self.vertices[i]);
+            }
+        }
+
+        for face in &mut self.faces_vert {
+            for id in face.iter_mut() {
+                *id = new_ids[*id as usize].unwrap();
+            }
+        }
+        self.vertices = vertices;
+        self.make_tris();
+    }
+
+    pub fn vertex_normals(&self) -> HashMap<u32, Normal> {
+        // Smooth normals: the normalized average of the normals of each vertex's
+        // faces. Where these cancel out, eg on a face shared by opposite cells,
+        // fall back to the first face's normal.
+        let mut sums: HashMap<u32, (Vertex, Normal)> = HashMap::new();
+        for (face, norm) in self.faces_vert.iter().zip(&self.normals) {
+            let n = Vertex::new(norm.normal.0, norm.normal.1, norm.normal.2, norm.normal.3);
+            for id in face {
+                let entry = sums.entry(*id).or_insert((Vertex::new(0., 0., 0., 0.), *norm));
+                entry.0 = entry.0.add(&n);
+            }
+        }
+
+        sums.into_iter().map(|(id, (sum, first))| {
+            let len = sum.dot(&sum).sqrt();
+            let norm = if len > 1e-6 {
+                let p = sum.scale(1. / len).position;
+                Normal::new(p.0, p.1, p.2, p.3)
+            } else { first };
+            (id, norm)
+        }).collect()
+    }
+
+    pub fn to_indexed(&self, smooth: bool) -> Result<IndexedMesh, MeshError> {
+        // Lay out the mesh for indexed drawing. With flat normals, a vertex is shared
+        // between faces whose normals match, eg across a flat grid; with smooth normals,
+        // every vertex appears once. Fails if a face's vertices or normal are missing.
+        self.check_indices()?;
+        let vertex_normals = if smooth { Some(self.vertex_normals()) } else { None };
+
+        let mut result = IndexedMesh { vertices: Vec::new(), normals: Vec::new(),
+            tris: Vec::new(), edges: Vec::new() };
+        let mut index_of: HashMap<(u32, [u32; 4]), u32> = HashMap::new();
+        let mut first_index: HashMap<u32, u32> = HashMap::new();
+        // The buffer index of each face's corners, in the order tris refers to them.
+        let mut corners = Vec::new();
+
+        for (face, face_norm) in self.faces_vert.iter().zip(&self.normals) {
+            for id in face {
+                let norm = match vertex_normals {
+                    Some(ref norms) => norms[id],
+                    None => *face_norm,
+                };
+                let n = norm.normal;
+                let key = (*id, [n.0.to_bits(), n.1.to_bits(), n.2.to_bits(), n.3.to_bits()]);
+
+                let index = match index_of.get(&key) {
+                    Some(i) => *i,
+                    None => {
+                        let i = result.vertices.len() as u32;
+                        result.vertices.push(self.vertices[*id as usize]);
+                        result.normals.push(norm);
+                        i
+                    }
+                };
+                index_of.insert(key, index);
+                first_index.entry(*id).or_insert(index);
+                corners.push(index);
+            }
+        }
+
+        result.tris = self.tris.iter().map(|corner| corners[*corner as usize]).collect();
+        for (a, b) in self.edges() {
+            result.edges.push(first_index[&a]);
+            result.edges.push(first_index[&b]);
+        }
+        Ok(result)
+    }
+
     pub fn to_bg(&self) -> MeshBg {
         let mut vertices = HashMap::new();
-        for (id, vert) in &self.vertices {
-            vertices.insert(*id, vec![vert.position.0, vert.position.1,
+        for (id, vert) in self.vertices.iter().enumerate() {
+            vertices.insert(id as u32, vec![vert.position.0, vert.position.1,
                                      vert.position.2, vert.position.3]);
         }
 
@@ -132,10 +680,42 @@ impl Mesh {
             faces_vert: self.faces_vert.iter().map(|face| face.to_vec()).collect(),
             normals,
             tris: self.tris.to_vec(),
+            cells: self.cells.iter().map(|cell| cell.to_vec()).collect(),
+            edges: self.edges().iter().map(|(a, b)| vec![*a, *b]).collect(),
         }
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct IndexedMesh {
+    // A mesh laid out for indexed drawing; each vertex/normal pair appears once, and
+    // tris and edges index into vertices and normals.
+    pub vertices: Vec<Vertex>,
+    pub normals: Vec<Normal>,
+    pub tris: Vec<u32>,
+    pub edges: Vec<u32>,  // Pairs of indices, for drawing as a line list.
+}
+
+#[derive(Clone, Debug)]
+pub struct MeshTransform {
+    // Places a mesh when combining it with others. As with a shape's model
+    // transform, we rotate, then scale, then shift by offset.
+    pub orientation: Array1<f32>,  // 6 items; one for each of the 4d hyperplanes.
+    pub scale: f32,
+    pub offset: [f32; 4],
+}
+
+impl MeshTransform {
+    pub fn new(orientation: Array1<f32>, scale: f32, offset: [f32; 4]) -> MeshTransform {
+        MeshTransform { orientation, scale, offset }
+    }
+
+    pub fn offset(offset: [f32; 4]) -> MeshTransform {
+        // Shift, without rotating or scaling.
+        MeshTransform { orientation: Array::zeros(6), scale: 1., offset }
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 //#[wasm_bindgen]
 pub struct MeshBg {
@@ -144,6 +724,8 @@ pub struct MeshBg {
     faces_vert: Vec<Vec<u32>>,  // Indicies of vertexes.
     normals: Vec<Vec<f32>>,  // Normals only use the 3d component; not defined for 4d, yet. ?
     tris: Vec<u32>,
+    cells: Vec<Vec<u32>>,  // Indices of faces.
+    edges: Vec<Vec<u32>>,  // Pairs of vertex ids.
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -157,6 +739,27 @@ pub struct ShapeBg {
     rotation_speed: Vec<f32>,  // 6 items, as with rotation.  Radians/s ?
     opacity: f32,
     specular_intensity: f32,
+    smooth_normals: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CoxeterGroup {
+    // Symmetry groups of the uniform 4-polytopes. Nodes of the Coxeter diagram
+    // are numbered from one end; for D4, node 1 is the branch point.
+    A4,  // [3, 3, 3]: The 5-cell family.
+    B4,  // [4, 3, 3]: Tesseract and 16-cell.
+    D4,  // [3^1,1,1]: Demitesseract.
+    F4,  // [3, 4, 3]: 24-cell.
+    H4,  // [5, 3, 3]: 120-cell and 600-cell.
+}
+
+#[derive(Clone, Debug)]
+pub struct FaceGroup {
+    // Faces of a uniform polytope that come from the same pair of mirrors in
+    // its Wythoff construction.
+    pub generators: (usize, usize),  // Indices of the two Coxeter diagram nodes.
+    pub sides: usize,
+    pub faces: Vec<usize>,  // Indices into the mesh's faces.
 }
 
 #[derive(Clone, Debug)]
@@ -170,6 +773,13 @@ pub struct Shape {
     pub rotation_speed: Array1<f32>,  // 6 items, as with rotation.  Radians/s ?
     pub opacity: f32,
     pub specular_intensity: f32,
+    // Shade with per-vertex normals averaged from adjacent faces, instead of one
+    // normal per face. Also lets more vertices share buffer entries.
+    pub smooth_normals: bool,
+    // If set, used instead of orientation, and rotation_speed spins it at a
+    // constant angular velocity. See rotor.rs. Native only: it isn't sent to the
+    // web frontend, which still uses orientation.
+    pub rotor: Option<Rotor4>,
 }
 
 impl Shape {
@@ -177,7 +787,18 @@ impl Shape {
                rotation_speed: Array1<f32>, opacity: f32) -> Shape {
 
         Shape{ mesh, position, scale: 1., orientation, rotation_speed,
-            opacity, specular_intensity: 1. }
+            opacity, specular_intensity: 1., smooth_normals: false, rotor: None }
+    }
+
+    pub fn turn(&mut self, plane: usize, amount: f32) {
+        // Rotate in one of the six planes orientation angles use, relative to the
+        // world's axes.
+        match self.rotor {
+            // Renormalize, so rounding error doesn't build up over many turns.
+            Some(rotor) => self.rotor = Some(
+                (Rotor4::from_angles(&plane_angles(plane, amount)) * rotor).normalize()),
+            None => self.orientation[plane] += amount,
+        }
     }
     
     pub fn to_bg(&self) -> ShapeBg {
@@ -189,6 +810,7 @@ impl Shape {
             rotation_speed: self.rotation_speed.to_vec(),
             opacity: self.opacity,
             specular_intensity: self.specular_intensity,
+            smooth_normals: self.smooth_normals,
         }
     }
 }
@@ -203,14 +825,33 @@ pub struct Camera {
     pub fov: f32,  // Vertical field of view in radians.
     pub aspect: f32,  // width / height.
     pub aspect_4: f32,  // fourth dim / height.
-    // near, far, and strange for our 3d and 4d frustrums.  Strange is an
-    // experimental extension into the 4th dimension.
+    // near and far for our 3d frustrum.
     pub near: f32,
     pub far: f32,
-    pub fourd_proj_dist: f32,
+    // How points are taken from 4d to 3d, before the 3d projection.
+    pub projection: Projection4,
+    // If set, the camera's orientation, used instead of θ. The view rotates the
+    // world by its inverse. Native only, as with Shape's rotor.
+    pub rotor: Option<Rotor4>,
+}
+
+fn plane_angles(plane: usize, amount: f32) -> Array1<f32> {
+    // Orientation angles for a turn in a single plane.
+    let mut result = Array::zeros(6);
+    result[plane] = amount;
+    result
 }
 
 impl Camera {
+    pub fn turn(&mut self, plane: usize, amount: f32) {
+        // Rotate in one of the six planes θ uses, relative to the camera's own axes.
+        match self.rotor {
+            Some(rotor) => self.rotor = Some(
+                (rotor * Rotor4::from_angles(&plane_angles(plane, amount))).normalize()),
+            None => self.θ[plane] += amount,
+        }
+    }
+
     pub fn view_size(&self, far: bool) -> (f32, f32){
         // Calculate the projected window width and height, using basic trig.
         let dist = if far { self.far } else { self.near };
@@ -221,6 +862,7 @@ impl Camera {
     }
 
     pub fn to_bg(&self) -> CameraBg {
+        let (projection_mode, projection_param) = self.projection.to_uniforms();
         CameraBg {
             position: self.position.to_vec(),
             θ: self.θ.to_vec(),
@@ -229,7 +871,8 @@ impl Camera {
             aspect_4: self.aspect_4,
             near: self.near,
             far: self.far,
-            fourd_proj_dist: self.fourd_proj_dist
+            projection_mode,
+            projection_param,
         }
     }
 }
@@ -245,7 +888,8 @@ pub struct CameraBg {
     aspect_4: f32,
     near: f32,
     far: f32,
-    fourd_proj_dist: f32,
+    projection_mode: i32,
+    projection_param: f32,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -328,6 +972,7 @@ pub struct Scene {
     pub lighting: Lighting,
     pub color_max: f32, // distance thresh for max 4d-color indicator.
     pub sensitivities: (f32, f32, f32),  // move, rotate, zoom
+    pub wireframe: bool,  // Draw edges instead of faces.
 }
 
 impl Scene {
@@ -345,6 +990,7 @@ impl Scene {
             color_max: self.color_max,
             sensitivities: vec![self.sensitivities.0, self.sensitivities.1,
                                   self.sensitivities.2],
+            wireframe: self.wireframe,
         }
     }
 }
@@ -361,5 +1007,289 @@ pub struct SceneBg {
     pub lighting: LightingBg,
     pub color_max: f32, // distance thresh for max 4d-color indicator.
     pub sensitivities: Vec<f32>,  // move, rotate, zoom
+    pub wireframe: bool,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face_mesh(coords: &[(f32, f32)]) -> Mesh {
+        // A single face on a tilted plane in 4d.
+        let mut vertices = HashMap::new();
+        for (id, &(a, b)) in coords.iter().enumerate() {
+            vertices.insert(id as u32, Vertex::new(a, b + a, b, -b));
+        }
+        let face = (0..coords.len() as u32).collect();
+        Mesh::new(vertices, vec![face], vec![Normal::new(0., 0., 0., 1.)])
+    }
+
+    fn tri_area(coords: &[(f32, f32)], tri: &[u32]) -> f32 {
+        let (a, b, c) = (coords[tri[0] as usize], coords[tri[1] as usize], coords[tri[2] as usize]);
+        ((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)) / 2.
+    }
+
+    fn square_mesh() -> (HashMap<u32, Vertex>, Vec<Array1<u32>>, Vec<Normal>) {
+        let mut vertices = HashMap::new();
+        vertices.insert(0, Vertex::new(0., 0., 0., 0.));
+        vertices.insert(1, Vertex::new(1., 0., 0., 0.));
+        vertices.insert(2, Vertex::new(1., 1., 0., 0.));
+        vertices.insert(3, Vertex::new(0., 1., 0., 0.));
+        (vertices, vec![array![0, 1, 2, 3]], vec![Normal::new(0., 0., 1., 0.)])
+    }
+
+    #[test]
+    fn test_vertex_ids() {
+        // Ids from a HashMap become indices, in order, with faces following them.
+        let (vertices, _, normals) = square_mesh();
+        let sparse: HashMap<u32, Vertex> = vertices.iter().map(|(id, v)| (id * 10 + 5, *v)).collect();
+        let mesh = Mesh::new(sparse, vec![array![15, 25, 35, 5]], normals.clone());
+        assert_eq!(mesh.faces_vert[0].to_vec(), vec![1, 2, 3, 0]);
+        assert_eq!(mesh.vertices[2].position, vertices[&2].position);
+
+        let list: Vec<Vertex> = (0..4).map(|id| vertices[&id]).collect();
+        let mesh = Mesh::new(list, vec![array![0, 1, 2, 3]], normals.clone());
+        assert_eq!(mesh.vertices[3].position, (0., 1., 0., 0.));
+
+        assert_eq!(Mesh::try_new(vertices, vec![array![0, 1, 7]], normals).unwrap_err(),
+                   MeshError::MissingVertex { face: 0, vertex: 7 });
+    }
+
+    #[test]
+    fn test_weld() {
+        // Two squares sharing an edge, with that edge's vertices doubled, and a
+        // sliver triangle that collapses once they're merged.
+        let (mut vertices, mut faces, mut normals) = square_mesh();
+        vertices.insert(4, Vertex::new(1., 0., 0., 0.));
+        vertices.insert(5, Vertex::new(2., 0., 0., 0.));
+        vertices.insert(6, Vertex::new(2., 1., 0., 0.));
+        vertices.insert(7, Vertex::new(1., 1. + 1e-7, 0., 0.));
+        vertices.insert(8, Vertex::new(5., 5., 5., 0.));  // Unused.
+        faces.push(array![4, 5, 6, 7]);
+        faces.push(array![1, 4, 2]);
+        normals.extend(vec![Normal::new(0., 0., 1., 0.); 2]);
+
+        let mut mesh = Mesh::new(vertices, faces, normals);
+        mesh.cells = vec![array![0, 1, 2]];
+        mesh.weld(1e-5);
+
+        assert_eq!(mesh.vertices.len(), 6);
+        assert_eq!(mesh.faces_vert.len(), 2);
+        assert_eq!(mesh.normals.len(), 2);
+        assert_eq!(mesh.cells[0].to_vec(), vec![0, 1]);
+        assert_eq!(mesh.faces_vert[1].to_vec(), vec![1, 4, 5, 2]);
+        assert_eq!(mesh.edges().len(), 7);
+        assert_eq!(mesh.tris.len(), 12);
+    }
+
+    #[test]
+    fn test_weld_pinched() {
+        // A face that loops through the same point twice once welded, making two
+        // triangles, and one whose two loops are slivers.
+        let vertices = vec![
+            Vertex::new(0., 0., 0., 0.), Vertex::new(1., 0., 0., 0.), Vertex::new(1., 1., 0., 0.),
+            Vertex::new(1e-7, 0., 0., 0.), Vertex::new(-1., 0., 0., 0.), Vertex::new(-1., -1., 0., 0.),
+            Vertex::new(0., 1e-7, 0., 0.),
+            Vertex::new(::std::f32::NAN, 0., 0., 0.),  // Unused, and can't be compared.
+        ];
+        let faces = vec![array![0, 1, 2, 3, 4, 5], array![0, 1, 6, 2]];
+        let mut mesh = Mesh::new(vertices, faces, vec![Normal::new(0., 0., 1., 0.); 2]);
+        mesh.cells = vec![array![0, 1]];
+        mesh.weld(1e-5);
+
+        assert_eq!(mesh.vertices.len(), 5);
+        let faces: Vec<Vec<u32>> = mesh.faces_vert.iter().map(|f| f.to_vec()).collect();
+        assert_eq!(faces, vec![vec![0, 1, 2], vec![0, 3, 4]]);
+        assert_eq!(mesh.normals.len(), 2);
+        assert_eq!(mesh.cells[0].to_vec(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_cross4() {
+        let a = Vertex::new(1., 2., 0.5, -1.);
+        let b = Vertex::new(0., 1., 3., 2.);
+        let c = Vertex::new(-2., 0.5, 1., 1.);
+        let n = a.cross4(&b, &c).normal;
+        let n = Vertex::new(n.0, n.1, n.2, n.3);
+        for v in &[a, b, c] {
+            assert!(n.dot(v).abs() < 1e-5);
+        }
+
+        // With u as the third vector, it's the 3d cross product.
+        let n = a.cross4(&b, &Vertex::new(0., 0., 0., 1.)).normal;
+        let n3 = a.cross(&b).normal;
+        assert_eq!(n, n3);
+    }
+
+    #[test]
+    fn test_validate() {
+        let (vertices, faces, normals) = square_mesh();
+        assert!(Mesh::try_new(vertices.clone(), faces.clone(), normals.clone()).is_ok());
+
+        let err = |vertices, faces, normals| Mesh::try_new(vertices, faces, normals).unwrap_err();
+
+        assert_eq!(err(vertices.clone(), vec![array![0, 1, 4]], normals.clone()),
+                   MeshError::MissingVertex { face: 0, vertex: 4 });
+        assert_eq!(err(vertices.clone(), vec![array![0, 1]], normals.clone()),
+                   MeshError::TooFewVertices { face: 0, count: 2 });
+        assert_eq!(err(vertices.clone(), faces.clone(), vec![]),
+                   MeshError::NormalCount { faces: 1, normals: 0 });
+
+        let mut bent = vertices.clone();
+        bent.insert(3, Vertex::new(0., 1., 0., 0.5));
+        assert_eq!(err(bent, faces.clone(), normals.clone()), MeshError::NonPlanarFace { face: 0 });
+
+        let mut doubled = vertices.clone();
+        doubled.insert(4, Vertex::new(1., 1., 0., 0.));
+        assert_eq!(err(doubled, faces.clone(), normals.clone()), MeshError::DuplicateVertices(2, 4));
+
+        let mut nan = vertices.clone();
+        nan.insert(1, Vertex::new(1., ::std::f32::NAN, 0., 0.));
+        assert_eq!(err(nan, faces.clone(), normals.clone()), MeshError::NonFiniteVertex(1));
+
+        // Laying out a mesh for drawing checks it has everything it needs.
+        let mut unlit = Mesh::new(vertices.clone(), faces.clone(), normals.clone());
+        unlit.normals.clear();
+        assert_eq!(unlit.to_indexed(false).unwrap_err(), MeshError::NormalCount { faces: 1, normals: 0 });
+
+        // Three triangles on one edge is fine in 4d, but not in 3d.
+        let mut fin = vertices.clone();
+        fin.insert(4, Vertex::new(0.5, 0.5, 1., 0.));
+        let fin_faces = vec![array![0, 1, 2], array![0, 2, 3], array![0, 2, 4]];
+        let fin_normals = vec![Normal::new(0., 0., 1., 0.); 3];
+        assert_eq!(err(fin.clone(), fin_faces.clone(), fin_normals.clone()),
+                   MeshError::NonManifoldEdge { edge: (0, 2), faces: 3 });
+
+        fin.insert(4, Vertex::new(0.5, 0.5, 0., 1.));
+        assert!(Mesh::try_new(fin, fin_faces, fin_normals).is_ok());
+    }
+
+    #[test]
+    fn test_cells() {
+        // A square pyramid, as a single cell.
+        let (mut vertices, mut faces, mut normals) = square_mesh();
+        vertices.insert(4, Vertex::new(0.5, 0.5, 1., 0.));
+        for i in 0..4 {
+            faces.push(array![i, (i + 1) % 4, 4]);
+            normals.push(Normal::new(0., 0., 1., 0.));
+        }
+        let mut mesh = Mesh::new(vertices, faces, normals);
+        assert_eq!(mesh.edges(), vec![(0, 1), (0, 3), (0, 4), (1, 2), (1, 4), (2, 3), (2, 4), (3, 4)]);
+
+        mesh.cells = vec![array![0, 1, 2, 3, 4]];
+        assert_eq!(mesh.validate(), Ok(()));
+        assert_eq!(mesh.cells_of_face(2), vec![0]);
+        assert!(mesh.cell_neighbors(0).is_empty());
+
+        mesh.cells = vec![array![0, 1, 2, 3]];
+        assert_eq!(mesh.validate(), Err(MeshError::OpenCell { cell: 0, edge: (0, 3) }));
+        mesh.cells = vec![array![0, 1, 2, 3, 4, 5]];
+        assert_eq!(mesh.validate(), Err(MeshError::MissingFace { cell: 0, face: 5 }));
+    }
+
+    #[test]
+    fn test_make_tris_convex() {
+        // Convex faces fan out from the first vertex.
+        let hexagon: Vec<(f32, f32)> = (0..6)
+            .map(|i| ((i as f32).cos(), (i as f32).sin())).collect();
+        let mesh = face_mesh(&hexagon);
+        assert_eq!(mesh.tris.to_vec(), vec![0, 1, 2, 0, 2, 3, 0, 3, 4, 0, 4, 5]);
+    }
+
+    #[test]
+    fn test_make_tris_concave() {
+        // Arrow heads with the concave corner at vertex 0 and 1, and a U shape.
+        for coords in &[
+            vec![(0., 0.), (-1., -1.), (0., 2.), (1., -1.)],
+            vec![(1., -1.), (0., 0.), (-1., -1.), (0., 2.)],
+            vec![(0., 0.), (3., 0.), (3., 3.), (2., 3.), (2., 1.), (1., 1.), (1., 3.), (0., 3.)],
+        ] {
+            let mesh = face_mesh(coords);
+            assert_eq!(mesh.tris.len(), 3 * (coords.len() - 2));
+
+            // The triangles all wind the same way as the polygon, and cover it exactly.
+            let n = coords.len();
+            let area = (0..n).fold(0., |acc, i| {
+                let (a, b) = (coords[i], coords[(i + 1) % n]);
+                acc + (a.0 * b.1 - b.0 * a.1) / 2.
+            });
+            let tris = mesh.tris.to_vec();
+            let mut total = 0.;
+            for tri in tris.chunks(3) {
+                let tri_area = tri_area(coords, tri);
+                assert!(tri_area * area > 0.);
+                total += tri_area;
+            }
+            assert!((total - area).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_make_tris_degenerate() {
+        // A repeated vertex doesn't give NaNs, and faces too short to have any
+        // triangles are skipped.
+        let mesh = face_mesh(&[(0., 0.), (0., 0.), (1., 0.), (1., 1.), (0., 1.)]);
+        assert_eq!(mesh.tris.len(), 9);
+        let flat = project_to_plane(&mesh.vertices);
+        assert!(flat.iter().all(|p| p.0.is_finite() && p.1.is_finite()));
+
+        let (vertices, _, _) = square_mesh();
+        let faces = vec![array![], array![0], array![0, 1], array![0, 1, 2]];
+        let mesh = Mesh::new(vertices, faces, vec![Normal::new(0., 0., 1., 0.); 4]);
+        assert_eq!(mesh.tris.to_vec(), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_to_indexed() {
+        // A 2x2 grid of squares: flat, then folded along its middle so the halves'
+        // normals differ.
+        let mut vertices = HashMap::new();
+        for i in 0..3 {
+            for j in 0..3 {
+                vertices.insert(i * 3 + j, Vertex::new(i as f32, 0., j as f32, 0.));
+            }
+        }
+        let faces = vec![array![0, 1, 4, 3], array![1, 2, 5, 4], array![3, 4, 7, 6], array![4, 5, 8, 7]];
+        let up = Normal::new(0., 1., 0., 0.);
+        let mesh = Mesh::new(vertices.clone(), faces.clone(), vec![up; 4]);
+
+        let indexed = mesh.to_indexed(false).unwrap();
+        assert_eq!(indexed.vertices.len(), 9);
+        assert_eq!(indexed.tris.len(), 24);
+        assert_eq!(indexed.edges.len(), 2 * 12);
+        // Each triangle still uses the positions the unindexed mesh does.
+        let corners: Vec<u32> = faces.iter().flat_map(|f| f.to_vec()).collect();
+        for (tri, corner) in indexed.tris.iter().zip(mesh.tris.iter()) {
+            let (a, b) = (indexed.vertices[*tri as usize].position,
+                          mesh.vertices[corners[*corner as usize] as usize].position);
+            assert_eq!(a, b);
+        }
+
+        let tilted = Normal::new(0.6, 0.8, 0., 0.);
+        let folded = Mesh::new(vertices, faces, vec![up, up, tilted, tilted]);
+        // The middle row of vertices has two normals.
+        assert_eq!(folded.to_indexed(false).unwrap().vertices.len(), 12);
+
+        let smooth = folded.to_indexed(true).unwrap();
+        assert_eq!(smooth.vertices.len(), 9);
+        let middle = smooth.vertices.iter().position(|v| v.position == (1., 0., 1., 0.)).unwrap();
+        let n = smooth.normals[middle].normal;
+        let len = (0.3f32 * 0.3 + 0.9 * 0.9).sqrt();
+        assert!((n.0 - 0.3 / len).abs() < 1e-6 && (n.1 - 0.9 / len).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_turn_normalizes() {
+        let mut shape = Shape::new(face_mesh(&[(0., 0.), (1., 0.), (0., 1.)]),
+            Array::zeros(4), Array::zeros(6), Array::zeros(6), 1.);
+        shape.rotor = Some(Rotor4::identity());
+        for i in 0..10000 {
+            shape.turn(i % 6, 0.0123);
+        }
+        let r = shape.rotor.unwrap();
+        let norm = (r.s.powi(2) + r.xy.powi(2) + r.xz.powi(2) + r.xu.powi(2) +
+            r.yz.powi(2) + r.yu.powi(2) + r.zu.powi(2) + r.xyzu.powi(2)).sqrt();
+        assert!((norm - 1.).abs() < 1e-5);
+    }
+}
@@ -1,15 +1,20 @@
 #version 450
 
 layout(location = 0) in vec4 position;
-layout(location = 1) in vec4 shape_posit;
-layout(location = 2) in vec4 normal;
-layout(location = 3) in float specular_intensity;
+layout(location = 1) in vec4 normal;
+layout(location = 2) in float specular_intensity;
 
 layout(location = 0) out vec4 color;
 
 layout(set = 0, binding = 0) uniform Data {
+    // Model and view are 4d affine transforms: a linear part, and a translation
+    // applied after it. These are Transform5s from transforms.rs, with the
+    // matrices uploaded through to_uniform_mat4.
     mat4 model;
+    vec4 model_translation;
+    mat4 normal_mat;  // The inverse transpose of model.
     mat4 view;
+    vec4 view_translation;
     mat4 proj;
     vec4 cam_position;
 
@@ -22,8 +27,30 @@ layout(set = 0, binding = 0) uniform Data {
 
     float color_max;
     float shape_opacity;
+
+    // How points are taken from 4d to 3d: 0 for orthographic, 1 for perspective
+    // (param is the eye's distance), 2 for stereographic (param is unused).
+    // See Projection4 in transforms.rs.
+    int projection_mode;
+    float projection_param;
 } uniforms;
 
+// Mirrors Projection4::project in transforms.rs; see PROJECT_4D_GLSL there.
+bool project_4d(vec4 pt, int mode, float param, out vec3 projected) {
+    float scale = 1.;
+    if (mode == 1) {
+        float denom = param + pt[3];
+        if (denom <= 0.) { return false; }
+        scale = param / denom;
+    } else if (mode == 2) {
+        float denom = length(pt) - pt[3];
+        if (denom <= 0.) { return false; }
+        scale = length(pt) / denom;
+    }
+    projected = pt.xyz * scale;
+    return true;
+}
+
 vec4 find_fourd_color(vec4 positioned_pt) {
     // calculate a color to represent position in the fourth dimension,
     // based on u dist between vertex and cam.
@@ -48,7 +75,7 @@ vec4 find_fourd_color(vec4 positioned_pt) {
 }
 
 vec4 find_diffuse_color() {
-    vec4 norm = normalize(uniforms.model * normalize(normal));
+    vec4 norm = normalize(uniforms.normal_mat * normalize(normal));
     vec4 dir = normalize(uniforms.diffuse_direction);
     // diffuse_weight is based on the andle of the face compared to the angle
     // of the incoming light.
@@ -57,13 +84,21 @@ vec4 find_diffuse_color() {
 }
 
 void main() {
-    // For model transform, position after the transform
-    vec4 positioned_pt = (uniforms.model * position) + shape_posit;
-    // for view transform, position first.
-    positioned_pt = uniforms.view * (positioned_pt - uniforms.cam_position);
-
-    // gl_Position is a builtin name used to output the projected point.
-    gl_Position = uniforms.proj * positioned_pt;
+    // The model transform moves the point to its place in the world, and the view
+    // transform from there to relative to the camera.
+    vec4 world_pt = (uniforms.model * position) + uniforms.model_translation;
+    vec4 positioned_pt = (uniforms.view * world_pt) + uniforms.view_translation;
+
+    // gl_Position is a builtin name used to output the projected point. The
+    // projection matrix's last column would act on u, which project_4d has
+    // already accounted for. Points with no 4d image go behind the near plane,
+    // so they're clipped instead of drawn far away.
+    vec3 projected;
+    if (project_4d(positioned_pt, uniforms.projection_mode, uniforms.projection_param, projected)) {
+        gl_Position = uniforms.proj * vec4(projected, 0.);
+    } else {
+        gl_Position = vec4(0., 0., -2., 1.);
+    }
 
     vec4 fourd_color = find_fourd_color(positioned_pt);
     vec4 diffuse_color = find_diffuse_color();